@@ -1,7 +1,10 @@
 fn main() {}
 
 fn app() -> Any {
-    Any { target: "./" }
+    Any {
+        target: "./",
+        watch: false,
+    }
 }
 
 fn window() -> Any {
@@ -20,6 +23,8 @@ fn canvas() -> Any {
         },
 
         cache_limit: 2,
+        /// 256 if missing
+        cache_mb: 256,
         pre_load_nums: 4,
 
         /// RGBA
@@ -35,6 +40,16 @@ fn page() -> Any {
         },
         img_resize_algo: WrapResizeAlg::Lanczos3,
         anim_resize_algo: WrapResizeAlg::Nearest,
+        /// 256 if missing
+        anim_mem_limit: 256,
+
+        /// HDR and EXR
+        tone_map: ToneMap::Aces,
+        exposure: 0.0,
+
+        /// ICC profiles -> sRGB, on if missing
+        color_management: true,
+        icc_output: None,
     }
 }
 
@@ -44,6 +59,14 @@ fn misc() -> Any {
     }
 }
 
+fn aseprite() -> Any {
+    Any {
+        tag: None,
+        hidden_layers: [],
+        show_slices: false,
+    }
+}
+
 fn once() -> Any {
     Any {
         record_gesture_name: None,
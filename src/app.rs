@@ -1,4 +1,5 @@
 pub mod buffer;
+pub mod cache;
 pub mod canvas;
 pub mod draw;
 pub mod gesture;
@@ -0,0 +1,96 @@
+use crate::*;
+
+use std::collections::VecDeque;
+
+/// `(file_index, sub_index, width, height)`
+type Key = (usize, usize, u32, u32);
+
+/// Decoded frames of pages that left the view.
+///
+/// The least recently used frame is dropped first when over the budget.
+pub struct FrameCache {
+    // oldest first
+    list: VecDeque<(Key, Frame, usize)>,
+    bytes: usize,
+    limit: usize,
+}
+
+impl FrameCache {
+    pub fn new(mb: usize) -> Self {
+        Self {
+            list: VecDeque::new(),
+            bytes: 0,
+            limit: mb * 1024 * 1024,
+        }
+    }
+
+    fn key(page: &Page) -> Key {
+        (
+            page.file_index,
+            page.sub_index,
+            page.dst_size.width() as u32,
+            page.dst_size.height() as u32,
+        )
+    }
+
+    /// Moves the frame of `page` into the cache, streams are not cached.
+    pub fn put(&mut self, page: &mut Page) {
+        let bytes = page.frame.byte_len();
+        if bytes == 0 || bytes > self.limit || matches!(page.frame.data, FrameData::Stream { .. }) {
+            return;
+        }
+
+        let key = Self::key(page);
+        self.remove(key);
+
+        self.list.push_back((key, page.frame.take(), bytes));
+        self.bytes += bytes;
+
+        while self.bytes > self.limit {
+            let Some((.., bytes)) = self.list.pop_front() else {
+                break;
+            };
+            self.bytes -= bytes;
+        }
+    }
+
+    /// Moves the cached frame back into `page`, returns `false` on miss.
+    pub fn restore(&mut self, page: &mut Page) -> bool {
+        let Some(frame) = self.remove(Self::key(page)) else {
+            return false;
+        };
+
+        tracing::debug!(action = "cache_hit", index = page.index);
+
+        page.frame = frame;
+        page.cast_vertex = page.frame.vertex;
+        page.state = State::Done;
+
+        true
+    }
+
+    fn remove(&mut self, key: Key) -> Option<Frame> {
+        let index = self.list.iter().position(|(k, ..)| *k == key)?;
+        let (_, frame, bytes) = self.list.remove(index)?;
+        self.bytes -= bytes;
+
+        Some(frame)
+    }
+
+    /// Drops frames which are not `f`.
+    pub fn retain(&mut self, f: impl Fn(&Frame) -> bool) {
+        self.list.retain(|(_, frame, _)| f(frame));
+        self.bytes = self.list.iter().map(|(.., bytes)| bytes).sum();
+    }
+
+    /// Drops frames of all pages of the file.
+    pub fn remove_file(&mut self, file_index: usize) {
+        self.list.retain(|((index, ..), ..)| *index != file_index);
+        self.bytes = self.list.iter().map(|(.., bytes)| bytes).sum();
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.bytes = 0;
+    }
+}
@@ -34,6 +34,11 @@ pub struct Canvas {
     cache_factor_up: f32,
     cache_factor_down: f32,
 
+    /// reading direction
+    forward: bool,
+    /// first and last page in the view
+    visible: (usize, usize),
+
     /// archive's info.
     pub top_line: f32,
 }
@@ -53,6 +58,8 @@ impl Canvas {
             offset: Vec2::default(),
             cache_factor_up: 0.5,
             cache_factor_down: 1.0,
+            forward: true,
+            visible: (0, 0),
 
             // flag_get_all_frame_size: false,
             top_line: 0.0,
@@ -76,12 +83,14 @@ impl Canvas {
         self.offset.y += self.step.y;
         self.cache_factor_up = 1.0;
         self.cache_factor_down = 0.5;
+        self.forward = false;
     }
 
     pub fn move_down(&mut self) {
         self.offset.y -= self.step.y;
         self.cache_factor_up = 0.5;
-        self.cache_factor_down = 1.0; // TODO: factor -> page nums
+        self.cache_factor_down = 1.0;
+        self.forward = true;
     }
 
     pub fn move_left(&mut self) {
@@ -130,6 +139,13 @@ impl Canvas {
 
 impl App {
     pub fn render(&mut self) -> eyre::Result<()> {
+        let width = self.page_size().width();
+        self.ext.pool.poll(&mut self.elems, &self.ext.data, width);
+
+        for page in self.elems.iter_mut() {
+            page.frame.data.set_playback(self.playback);
+        }
+
         match &self.action {
             Action::Gesture { .. } => {
                 self.draw_gesture_path();
@@ -137,6 +153,12 @@ impl App {
                 return Ok(());
             }
 
+            Action::Password { .. } => {
+                self.canvas.reset();
+
+                return Ok(());
+            }
+
             _ => {}
         }
 
@@ -151,6 +173,8 @@ impl App {
             _ => todo!(),
         }
 
+        self.ext.pool.cancel_unused(&self.elems);
+
         Ok(())
     }
 }
@@ -186,15 +210,20 @@ impl App {
         let view_area = self.view_area(&self.layout);
 
         let mut elems: Vec<&mut Page> = Vec::with_capacity(10);
+        let mut pending: Vec<&mut Page> = Vec::new();
+        let mut visible = None;
         let mut page_offset: Vec2<f32> = Vec2::default();
 
+        // 0. read the sizes in the background
+        queue_sizes(&mut self.ext, &view_area, &self.elems);
+
         'l: for page in self.elems.iter_mut() {
-            // page.size()
+            // pages after it are laid out once the size is known
             if page.frame.size.is_zero() {
-                page.load(&self.ext.data, false)?;
+                break 'l;
+            }
+            if page.dst_size.is_zero() {
                 page.dst_size = page.frame.size.resize_by_width(page_size.width());
-
-                return Ok(());
             }
 
             // dbg!(&page.index, &page.cast_vertex);
@@ -214,7 +243,12 @@ impl App {
             let h = page.dst_size.height();
             page_offset.y += h;
 
-            let flag = page.is_passed(&self.config, &view_area);
+            let (is_hover_edge, is_hover_view) = view_area.is_page_hover(page);
+            if is_hover_view {
+                visible = update_visible(visible, page.index);
+            }
+
+            let flag = page.is_passed(&self.config, &view_area, &mut self.ext.cache);
             if !flag {
                 // dbg!("skip", page.index);
 
@@ -223,27 +257,28 @@ impl App {
 
             match page.state {
                 // 4. draw
-                State::Done => {
+                // pre-loaded pages are not drawn
+                State::Done | State::Failed(_) if is_hover_edge => {
                     elems.push(page);
                 }
 
                 // 3. loading
                 State::Loading => {
-                    let data = self.ext.data.clone();
-
-                    if page.frame.size.is_zero() {
-                        self.ext.pool.task_load(page, data);
-                    } else {
-                        page.dst_size = page.frame.size.resize_by_width(page_size.width());
+                    page.dst_size = page.frame.size.resize_by_width(page_size.width());
 
-                        self.ext.pool.task_resize(page, data, &self.config);
-                    }
+                    pending.push(page);
                 }
 
                 _ => {}
             }
         }
 
+        // 5. decode the nearest first
+        resize_pages(&mut self.ext, &self.config, &view_area, pending);
+        if let Some(visible) = visible {
+            self.canvas.visible = visible;
+        }
+
         // TODO:
         // if let Some(nav) = self.canvas.ui_nav_bar {
         //     elems.push(nav);
@@ -272,6 +307,20 @@ impl App {
 
     // FIXME: bug in fullscreen
     pub fn view_area(&self, layout: &Layout) -> ViewArea {
+        // `pre_load_nums` pages ahead, and half of them behind
+        let (visible, forward) = (self.canvas.visible, self.canvas.forward);
+        let ahead = self.config.canvas.pre_load_nums as usize;
+        let behind = ahead / 2;
+        let pre_load = {
+            let (first, last) = visible;
+
+            if forward {
+                (first.saturating_sub(behind), last + ahead)
+            } else {
+                (first.saturating_sub(ahead), last + behind)
+            }
+        };
+
         // screen coordinate system:
         match layout {
             //
@@ -308,7 +357,13 @@ impl App {
                 border.min.y -= top;
                 border.max.y += buttom;
 
-                ViewArea { view, border }
+                ViewArea {
+                    view,
+                    border,
+                    visible,
+                    pre_load,
+                    forward,
+                }
             }
 
             //
@@ -353,9 +408,16 @@ impl App {
                 border.min.y -= top;
                 border.max.y += buttom;
 
-                ViewArea { view, border }
+                ViewArea {
+                    view,
+                    border,
+                    visible,
+                    pre_load,
+                    forward,
+                }
             }
 
+            // Single shows only the first page, so there is nothing to prefetch.
             _ => todo!(),
         }
     }
@@ -404,19 +466,37 @@ impl App {
         if page.frame.size.is_zero() {
             let algo = fir::ResizeAlg::Nearest;
 
-            page.load(&self.ext.data, true)?;
+            let res = page.load(&self.ext.data, true).and_then(|_| {
+                page.dst_size = page.frame.size.resize_by_width(page_size.width());
 
-            page.dst_size = page.frame.size.resize_by_width(page_size.width());
+                Frame::resize_nth(
+                    page.tmp_blob.as_slice(),
+                    page.sub_index,
+                    page.dst_size,
+                    algo,
+                    algo,
+                    page.frame.ase.clone(),
+                )
+            });
+
+            match res {
+                Ok(frame) => {
+                    page.frame = frame;
+                    page.state = State::Done;
+                }
 
-            let frame = Frame::resize(page.tmp_blob.as_slice(), page.dst_size, algo)?;
+                Err(e) => {
+                    let name = self.ext.data.file_name(page.file_index);
+
+                    page.set_failed(&name, e.to_string(), page_size.width());
+                }
+            }
 
             // align
             let drag_offset = center_xy(size, page.dst_size);
 
-            page.frame = frame;
             page.frame.vertex = page.frame.vertex.translate(drag_offset.x, drag_offset.y);
             page.cast_vertex = page.frame.vertex;
-            page.state = State::Done;
         }
 
         // TODO: flag_key
@@ -425,7 +505,7 @@ impl App {
         self.canvas.offset = Default::default();
 
         // REFS: http://phrogz.net/tmp/canvas_zoom_to_cursor.html
-        if *flag_scroll {
+        if *flag_scroll && page.state == State::Done {
             // TODO: align
 
             let algo = fir::ResizeAlg::Nearest;
@@ -433,13 +513,19 @@ impl App {
             let factor = scale.powf(*dire);
             let dst_size = page.dst_size * factor;
 
-            let frame = Frame::resize(page.tmp_blob.as_slice(), dst_size, algo)?;
+            let frame = Frame::resize_nth(
+                page.tmp_blob.as_slice(),
+                page.sub_index,
+                dst_size,
+                algo,
+                algo,
+                page.frame.ase.take(),
+            )?;
             page.frame = frame;
             page.dst_size = dst_size;
             page.zoom_at(*mouse_pos, factor);
-
-            *flag_scroll = false;
         }
+        *flag_scroll = false;
 
         page.draw(&mut self.canvas.buffer);
 
@@ -602,20 +688,25 @@ impl App {
         let r43 = 4.0 / 3.0;
 
         let mut draw_elems: Vec<&mut Page> = Vec::with_capacity(10);
+        let mut pending: Vec<&mut Page> = Vec::new();
+        let mut visible = None;
         let mut elem_offset: Vec2<f32> = Vec2::default();
         let mut elem_rank = 0;
         let mut head_size = Default::default();
 
+        queue_sizes(&mut self.ext, &view_area, &self.elems);
+
         'l: for elem in self.elems.iter_mut() {
+            // pages after it are laid out once the size is known
             if elem.frame.size.is_zero() {
-                elem.load(&self.ext.data, false)?;
+                break 'l;
+            }
+            if elem.dst_size.is_zero() {
                 elem.dst_size = elem.frame.size.resize_by_width(max_w);
                 elem.cast_vertex = Rect::new_at_zero(elem.dst_size);
-
-                return Ok(());
             }
 
-            let flag = elem.is_passed(&self.config, &view_area);
+            let flag = elem.is_passed(&self.config, &view_area, &mut self.ext.cache);
             if !flag {
                 // dbg!("skip", elem.index);
 
@@ -673,35 +764,40 @@ impl App {
 
             elem.drag(drag_offset);
 
+            let (is_hover_edge, is_hover_view) = view_area.is_page_hover(elem);
+            if is_hover_view {
+                visible = update_visible(visible, elem.index);
+            }
+
             match elem.state {
-                State::Done => {
+                // pre-loaded pages are not drawn
+                State::Done | State::Failed(_) if is_hover_edge => {
                     draw_elems.push(elem);
                 }
 
                 State::Loading => {
-                    let data = self.ext.data.clone();
-
-                    if elem.frame.size.is_zero() {
-                        self.ext.pool.task_load(elem, data);
-                    } else {
-                        let w = {
-                            if elem.frame.size.ratio() > r43 {
-                                max_w
-                            } else {
-                                min_w
-                            }
-                        };
-
-                        elem.dst_size = elem.frame.size.resize_by_width(w);
-
-                        self.ext.pool.task_resize(elem, data, &self.config);
-                    }
+                    let w = {
+                        if elem.frame.size.ratio() > r43 {
+                            max_w
+                        } else {
+                            min_w
+                        }
+                    };
+
+                    elem.dst_size = elem.frame.size.resize_by_width(w);
+
+                    pending.push(elem);
                 }
 
                 _ => {}
             }
         }
 
+        resize_pages(&mut self.ext, &self.config, &view_area, pending);
+        if let Some(visible) = visible {
+            self.canvas.visible = visible;
+        }
+
         for elem in draw_elems.iter_mut() {
             elem.draw(&mut self.canvas.buffer);
         }
@@ -711,6 +807,35 @@ impl App {
     }
 }
 
+// Reads the sizes of all pages, files of the pre-loaded ones are kept for decoding.
+fn queue_sizes(ext: &mut Ext, view_area: &ViewArea, pages: &[Page]) {
+    for page in pages.iter().filter(|page| page.frame.size.is_zero()) {
+        let keep_blob = view_area.is_pre_load(page.index);
+
+        ext.pool.task_load(page, ext.data.clone(), keep_blob);
+    }
+}
+
+// Decodes pages nearest to the view first, or takes them from the cache.
+fn resize_pages(ext: &mut Ext, config: &Config, view_area: &ViewArea, mut pages: Vec<&mut Page>) {
+    pages.sort_by_key(|page| view_area.distance(page.index));
+
+    for page in pages {
+        if ext.cache.restore(page) {
+            page.tmp_blob = Vec::new();
+        } else {
+            ext.pool.task_resize(page, ext.data.clone(), config);
+        }
+    }
+}
+
+fn update_visible(visible: Option<(usize, usize)>, index: usize) -> Option<(usize, usize)> {
+    match visible {
+        Some((first, last)) => Some((first.min(index), last.max(index))),
+        None => Some((index, index)),
+    }
+}
+
 // REFS: https://www.codeandweb.com/texturepacker
 // REFS: https://www.david-colson.com/2020/03/10/exploring-rect-packing.html
 // REFS: https://codeincomplete.com/articles/bin-packing/
@@ -44,7 +44,12 @@ impl Gesture {
             gest_file.read_to_end(&mut buf)?;
 
             let name = gest_file.name().to_string();
-            temps.push(load_gesture(name, buf.as_slice())?);
+            match load_gesture(name, buf.as_slice()) {
+                Ok(temp) => temps.push(temp),
+
+                // skip broken entries
+                Err(e) => tracing::warn!(action = "load_gesture", error = %e),
+            }
         }
 
         Ok(Self {
@@ -123,6 +128,10 @@ fn path_to_gest(data: &[Vec2]) -> Path2D {
 }
 
 fn load_gesture(name: String, data: &[u8]) -> eyre::Result<Template> {
+    if data.is_empty() || data.len() % 8 != 0 {
+        eyre::bail!("ERROR: Broken gesture: {name}");
+    }
+
     let mut gest = Path2D::default();
 
     for v in data.chunks(8).into_iter() {
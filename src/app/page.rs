@@ -3,7 +3,6 @@ use std::ops::Neg;
 use crate::*;
 
 use fir::ResizeAlg;
-use imagesize::blob_size;
 
 use self::affine::Affine;
 
@@ -17,6 +16,10 @@ pub struct Page {
 
     pub state: State,
     pub index: usize,
+    /// index in `DataType`
+    pub file_index: usize,
+    /// page of multi-page formats, e.g. TIFF
+    pub sub_index: usize,
     pub style: Style,
 
     pub tmp_blob: Vec<u8>,
@@ -29,6 +32,9 @@ pub enum State {
 
     Loading,
     Done,
+
+    /// failed to load or decode
+    Failed(String),
 }
 
 impl Page {
@@ -57,7 +63,6 @@ impl Page {
             )
         };
 
-        // FIXME(gif): missed first frame
         let frame = self.frame.next_frame().to_argb_bytes();
         // let tolerance = 1;
 
@@ -92,21 +97,18 @@ impl Page {
         }
     }
 
-    pub fn new_empty(index: usize) -> Self {
+    pub fn new_empty(index: usize, file_index: usize) -> Self {
         Self {
             index,
+            file_index,
             ..Default::default()
         }
     }
 
     pub fn load(&mut self, data: &DataType, flag_cache: bool) -> eyre::Result<()> {
-        let blob = data.get_file(self.index)?;
+        let blob = data.get_file(self.file_index)?;
 
-        // let ty = image_type(blob.as_slice())?;
-        let size = blob_size(blob.as_slice())?;
-        let w = size.width as f32;
-        let h = size.height as f32;
-        let size = Size::new(w, h);
+        let (.., size) = Frame::probe_nth(blob.as_slice(), self.sub_index)?;
 
         self.frame.size = size;
         self.frame.vertex = Rect::new_at_zero(size);
@@ -118,12 +120,24 @@ impl Page {
         Ok(())
     }
 
-    pub fn resize(&mut self, data: &DataType, algo: ResizeAlg) -> eyre::Result<()> {
+    pub fn resize(
+        &mut self,
+        data: &DataType,
+        algo: ResizeAlg,
+        anim_algo: ResizeAlg,
+    ) -> eyre::Result<()> {
         if self.tmp_blob.is_empty() {
-            self.tmp_blob = data.get_file(self.index)?;
+            self.tmp_blob = data.get_file(self.file_index)?;
         }
 
-        let frame = Frame::resize(self.tmp_blob.as_slice(), self.dst_size, algo)?;
+        let frame = Frame::resize_nth(
+            self.tmp_blob.as_slice(),
+            self.sub_index,
+            self.dst_size,
+            algo,
+            anim_algo,
+            self.frame.ase.take(),
+        )?;
 
         self.frame = frame;
         self.cast_vertex = self.frame.vertex;
@@ -135,6 +149,37 @@ impl Page {
         Ok(())
     }
 
+    /// Shows a placeholder with `name` and `reason`, which is `width` wide if
+    /// the size is unknown.
+    pub fn set_failed(&mut self, name: &str, reason: String, width: f32) {
+        tracing::error!(action = "failed", index = self.index, reason = %reason);
+
+        let size = {
+            if !self.dst_size.is_zero() {
+                self.dst_size
+            } else {
+                Size::new(width.max(1.0), (width / 2.0).max(1.0))
+            }
+        };
+
+        self.frame = Frame::placeholder(
+            size,
+            format!("{name}\n{reason}\n\npress `r` to retry").as_str(),
+        );
+        self.dst_size = size;
+        self.cast_vertex = self.frame.vertex;
+        self.state = State::Failed(reason);
+    }
+
+    /// Loads the page again if it failed.
+    pub fn retry(&mut self) {
+        if let State::Failed(_) = self.state {
+            self.frame = Frame::default();
+            self.dst_size = Size::default();
+            self.state = State::Empty;
+        }
+    }
+
     pub fn free(&mut self) {
         self.frame.free();
         self.state = State::Empty;
@@ -170,8 +215,14 @@ impl Page {
         self.index + 1
     }
 
-    pub fn is_passed(&mut self, config: &Config, view_area: &ViewArea) -> bool {
+    pub fn is_passed(
+        &mut self,
+        config: &Config,
+        view_area: &ViewArea,
+        cache: &mut FrameCache,
+    ) -> bool {
         let (is_hover_edge, is_hover_view) = view_area.is_page_hover(&self);
+        let is_hover_edge = is_hover_edge || view_area.is_pre_load(self.index);
 
         // dbg!(is_hover_edge, is_hover_view);
         match (&mut self.state, is_hover_edge, is_hover_view) {
@@ -185,13 +236,23 @@ impl Page {
                     index = ?self.index
                 );
 
+                cache.put(self);
                 self.free();
                 self.state = State::Empty;
 
                 false
             }
 
+            // placeholder
+            (State::Failed(_), true, ..) => true,
+
             // 1.2. resize()
+            (State::Loading, false, false) => {
+                // cancelled by `Pool::cancel_unused()`
+                self.state = State::Empty;
+
+                false
+            }
             (State::Loading, ..) => true,
 
             // 1. loading
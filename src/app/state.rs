@@ -28,6 +28,12 @@ pub enum Action {
         path: Vec<Vec2>,
     },
 
+    /// Waiting for the password of an encrypted archive.
+    Password {
+        input: String,
+        wrong: bool,
+    },
+
     // TODO:
     // 1. toggle selection mode
     //    if Key::S {
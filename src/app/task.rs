@@ -1,95 +1,243 @@
 use crate::*;
 
-use std::sync::{Arc, OnceLock, RwLock};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        OnceLock,
+    },
+};
 
 pub static THREAD_GET_ALL_FRAME_SIZE: OnceLock<Vec<Page>> = OnceLock::new();
 
+/// Loads and decodes pages in background threads.
+///
+/// Results are sent back over a channel and applied by `poll()`.
 pub struct Pool {
     inner: rayon::ThreadPool,
-    list: Arc<RwLock<Vec<Page>>>,
+
+    tx: Sender<TaskRes>,
+    rx: Receiver<TaskRes>,
+
+    /// in-flight jobs, by `Page.index`
+    jobs: HashMap<usize, Job>,
+    next_id: u64,
+}
+
+struct Job {
+    id: u64,
+    kind: TaskKind,
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TaskKind {
+    Load,
+    Resize { dst_size: Size },
+}
+
+struct TaskRes {
+    id: u64,
+    index: usize,
+    out: TaskOut,
+}
+
+enum TaskOut {
+    Load(eyre::Result<(Size, Vec<u8>)>),
+    Resize(eyre::Result<Frame>),
 }
 
 impl Pool {
-    pub fn new(elems: Vec<Page>) -> Self {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+
         Self {
             inner: rayon::ThreadPoolBuilder::new().build().unwrap(),
-            list: Arc::new(RwLock::new(elems)),
+            tx,
+            rx,
+            jobs: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Cancels all jobs, e.g. after the directory has been rescanned.
+    ///
+    /// Threads are kept, results of the cancelled jobs are dropped with the old channel.
+    pub fn reset(&mut self) {
+        for (_, job) in self.jobs.drain() {
+            job.cancel.store(true, Ordering::Relaxed);
         }
+
+        (self.tx, self.rx) = mpsc::channel();
     }
 
-    pub fn task_load(&self, page: &mut Page, data: Arc<DataType>) {
-        let index = page.index;
-        let list = self.list.clone();
+    /// Reads the size of `page`.
+    ///
+    /// The file is kept for `task_resize()` if `keep_blob` is `true`.
+    pub fn task_load(&mut self, page: &Page, data: Arc<DataType>, keep_blob: bool) {
+        let Some((id, cancel)) = self.add_job(page.index, TaskKind::Load) else {
+            return;
+        };
+        let mut task = detach(page);
+        let tx = self.tx.clone();
 
         self.inner.spawn(move || {
-            let Ok(list) = &mut list.try_write() else {
+            if cancel.load(Ordering::Relaxed) {
                 return;
-            };
-            let task = &mut list[index];
-
-            if task.frame.size.is_zero() {
-                task.load(&data, true).unwrap();
-                // dbg!(task.frame.size);
             }
+
+            let res = task
+                .load(&data, keep_blob)
+                .map(|_| (task.frame.size, mem::take(&mut task.tmp_blob)));
+            let out = TaskOut::Load(res);
+
+            let _ = tx.send(TaskRes {
+                id,
+                index: task.index,
+                out,
+            });
         });
+    }
 
-        let Ok(list) = self.list.try_read() else {
+    /// Decodes `page` at `page.dst_size`.
+    pub fn task_resize(&mut self, page: &mut Page, data: Arc<DataType>, config: &Config) {
+        if page.dst_size.is_zero() {
             return;
-        };
-        let task = &list[index];
-
-        if !task.frame.size.is_zero() {
-            page.frame.size = task.frame.size;
-            page.state = State::Loading;
         }
-    }
 
-    pub fn task_resize(&self, page: &mut Page, data: Arc<DataType>, config: &Config) {
-        let list = self.list.clone();
+        let kind = TaskKind::Resize {
+            dst_size: page.dst_size,
+        };
+        let Some((id, cancel)) = self.add_job(page.index, kind) else {
+            return;
+        };
 
         let algo_image = config.page_img_resize_algo();
         let algo_anim = config.page_anim_resize_algo();
 
-        let index = page.index;
-        let dst_size = page.dst_size;
+        let mut task = detach(page);
+        task.tmp_blob = mem::take(&mut page.tmp_blob);
+        let tx = self.tx.clone();
 
         self.inner.spawn(move || {
-            let Ok(list) = &mut list.try_write() else {
+            if cancel.load(Ordering::Relaxed) {
                 return;
-            };
-            let task = &mut list[index];
-
-            if task.state == State::Empty && !dst_size.is_zero() {
-                let algo = {
-                    if task.frame.ty() == FrameTy::Img {
-                        algo_image
-                    } else {
-                        algo_anim
-                    }
-                };
-
-                task.dst_size = dst_size;
-                task.resize(&data, algo).unwrap();
-                task.state = State::Done;
-
-                // dbg!("resize", task.index, task.frame.size);
             }
+
+            let res = task
+                .resize(&data, algo_image, algo_anim)
+                .map(|_| mem::take(&mut task.frame));
+            let out = TaskOut::Resize(res);
+
+            let _ = tx.send(TaskRes {
+                id,
+                index: task.index,
+                out,
+            });
         });
+    }
 
-        let Ok(list) = &mut self.list.try_write() else {
-            return;
-        };
-        let task = &mut list[index];
+    // `None` if the same job is in flight, others are replaced.
+    fn add_job(&mut self, index: usize, kind: TaskKind) -> Option<(u64, Arc<AtomicBool>)> {
+        if let Some(job) = self.jobs.get(&index) {
+            if job.kind == kind {
+                return None;
+            }
 
-        if task.state == State::Done {
-            mem::swap(task, page);
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
 
-            task.free();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.jobs.insert(
+            index,
+            Job {
+                id,
+                kind,
+                cancel: cancel.clone(),
+            },
+        );
+
+        Some((id, cancel))
+    }
 
-            // TODO: Align
-            // let ew = page.dst_size.width();
-            // let padding_left = center_x(cw, ew);
-            // page.frame.vertex = page.frame.vertex.translate(padding_left, 0.0);
+    /// Applies finished jobs to `elems`.
+    ///
+    /// Failed pages are `width` wide if the size is unknown.
+    pub fn poll(&mut self, elems: &mut [Page], data: &DataType, width: f32) {
+        while let Ok(TaskRes { id, index, out }) = self.rx.try_recv() {
+            // cancelled or replaced
+            if self.jobs.get(&index).map(|job| job.id) != Some(id) {
+                continue;
+            }
+            self.jobs.remove(&index);
+
+            let Some(page) = elems.get_mut(index) else {
+                continue;
+            };
+
+            // sizes are read before the pages are loading
+            let is_wanted = match out {
+                TaskOut::Load(_) => page.frame.size.is_zero(),
+                TaskOut::Resize(_) => page.state == State::Loading,
+            };
+            if !is_wanted {
+                continue;
+            }
+
+            match out {
+                TaskOut::Load(Ok((size, blob))) => {
+                    page.frame.size = size;
+                    page.frame.vertex = Rect::new_at_zero(size);
+                    page.tmp_blob = blob;
+                }
+
+                TaskOut::Resize(Ok(frame)) => {
+                    page.frame = frame;
+                    page.cast_vertex = page.frame.vertex;
+                    page.state = State::Done;
+                }
+
+                TaskOut::Load(Err(e)) | TaskOut::Resize(Err(e)) => {
+                    page.set_failed(&data.file_name(page.file_index), e.to_string(), width);
+                }
+            }
         }
     }
+
+    /// Cancels jobs of pages which are not loading anymore, e.g. scrolled away.
+    ///
+    /// Sizes are always read, the layout needs all of them.
+    pub fn cancel_unused(&mut self, elems: &[Page]) {
+        self.jobs.retain(|index, job| {
+            let keep = elems.get(*index).is_some_and(|page| match job.kind {
+                TaskKind::Load => page.frame.size.is_zero(),
+                TaskKind::Resize { .. } => page.state == State::Loading,
+            });
+            if !keep {
+                job.cancel.store(true, Ordering::Relaxed);
+            }
+
+            keep
+        });
+    }
+}
+
+// Copy of `page` without pixels, owned by the job.
+fn detach(page: &Page) -> Page {
+    Page {
+        index: page.index,
+        file_index: page.file_index,
+        sub_index: page.sub_index,
+        dst_size: page.dst_size,
+        frame: Frame {
+            size: page.frame.size,
+            ase: page.frame.ase.clone(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
 }
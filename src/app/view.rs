@@ -4,13 +4,20 @@ use crate::*;
 pub struct ViewArea<T = Rect> {
     pub view: T,
     pub border: T,
+
+    /// first and last page in the view, of the last frame
+    pub visible: (usize, usize),
+    /// pages kept loaded besides `border`
+    pub pre_load: (usize, usize),
+    /// reading direction
+    pub forward: bool,
 }
 
 impl ViewArea {
     pub fn is_page_hover(&self, page: &Page) -> (bool, bool) {
         // dbg!(&self, page.cast_vertex);
 
-        let Self { view, border } = *self;
+        let Self { view, border, .. } = *self;
         let page = &page.cast_vertex;
 
         let is_hover_edge = {
@@ -33,4 +40,26 @@ impl ViewArea {
 
         (is_hover_edge, is_hover_view)
     }
+
+    pub fn is_pre_load(&self, index: usize) -> bool {
+        (self.pre_load.0..=self.pre_load.1).contains(&index)
+    }
+
+    /// `0` in the view, pages ahead come before pages behind at the same distance.
+    pub fn distance(&self, index: usize) -> usize {
+        let (first, last) = self.visible;
+        let (ahead, behind) = {
+            if self.forward {
+                (index.saturating_sub(last), first.saturating_sub(index))
+            } else {
+                (first.saturating_sub(index), index.saturating_sub(last))
+            }
+        };
+
+        match (ahead, behind) {
+            (0, 0) => 0,
+            (0, d) => d * 2,
+            (d, _) => d * 2 - 1,
+        }
+    }
 }
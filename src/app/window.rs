@@ -4,10 +4,14 @@ use eyre::OptionExt;
 use rgb::RGBA8;
 use softbuffer::{Context, Surface};
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     num::NonZeroU32,
     rc::Rc,
-    sync::{Arc, Mutex},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
     time::{Duration, Instant},
 };
 use winit::{
@@ -36,10 +40,14 @@ pub struct App {
     pub event_info: EventInfo,
     pub surface: Surface<Rc<Window>, Rc<Window>>,
     pub monitor: Option<MonitorHandle>,
+
+    pub playlist: Playlist,
+    pub playback: Playback,
 }
 
 pub struct Ext {
     pub pool: Pool,
+    pub cache: FrameCache,
     pub data: Arc<DataType>, // TODO: mv to App
 }
 
@@ -51,8 +59,22 @@ pub struct Init {
 pub struct EnvData {
     flag_gesture: bool,
     flag_fullscreen: bool,
+    /// scrolled to the end of the target once
+    flag_end: bool,
 
     loop_dur: Duration,
+
+    last_scan: Instant,
+    scan_dur: Duration,
+
+    /// failed page in the title
+    last_failed: Option<usize>,
+
+    /// directory rescan in flight
+    scan: Option<Receiver<Option<DataType>>>,
+
+    /// stdin can be read only once, kept for the playlist
+    stdin: Option<DataType>,
 }
 
 pub struct EventInfo {
@@ -64,6 +86,7 @@ impl App {
         let (mut app, event_loop) = Self::new(config)?;
 
         app.window().set_ime_allowed(true);
+        app.update_title();
         if let Some(name) = app.config.once.record_gesture_name.as_ref() {
             app.run_record_gesture(event_loop)?;
         } else {
@@ -80,13 +103,37 @@ impl App {
     fn new(config: Config) -> eyre::Result<(Self, EventLoop<()>)> {
         let gestures = Gesture::load(config.gestures.data_path.as_str())?;
 
-        let (data, pool, elems);
+        let playlist = Playlist::new(config.app.targets.clone());
+        frame::hdr::set_tone_map(config.page.tone_map, config.page.exposure);
+        frame::anim::set_mem_limit(*config.page.anim_mem_limit as usize);
+        frame::aseprite::set_opts(
+            config.aseprite.tag.clone(),
+            config.aseprite.hidden_layers.clone(),
+            config.aseprite.show_slices,
+        );
+        frame::icc::init(
+            *config.page.color_management,
+            config.page.icc_output.as_deref(),
+        )?;
+
+        let (data, pool, elems, action);
+        let mut stdin = None;
         let canvas = {
             let path = config.app.target.as_path();
-            data = DataType::new(path)?;
-            let empty_pages = data.gen_empty_pages(config.misc.padding_filename as usize)?;
+            let mut tmp = match config.app.list.as_ref() {
+                Some(list) => DataType::from_list(list)?,
+                None => DataType::new(path)?,
+            };
 
-            pool = Pool::new(empty_pages.clone());
+            let empty_pages;
+            (empty_pages, action) = prepare_data(&config, &mut tmp)?;
+            data = tmp;
+
+            if path == Path::new("-") && config.app.list.is_none() {
+                stdin = Some(data.clone());
+            }
+
+            pool = Pool::new();
             elems = empty_pages;
 
             Canvas::new(&config)?
@@ -112,13 +159,17 @@ impl App {
         Ok((
             Self {
                 init: Default::default(),
-                action: Default::default(),
-                env: EnvData::new(),
+                action,
+                env: EnvData {
+                    stdin,
+                    ..EnvData::new()
+                },
                 event_info: EventInfo::new(),
                 world: World::new(elems.clone()),
                 elems, // TODO: rm
                 ext: Ext {
                     pool,
+                    cache: FrameCache::new(*config.canvas.cache_mb as usize),
                     data: Arc::new(data),
                 },
                 monitor: window.current_monitor(),
@@ -127,11 +178,223 @@ impl App {
                 surface,
                 config,
                 gestures,
+                playlist,
+                playback: Playback::default(),
             },
             event_loop,
         ))
     }
 
+    /// Replaces the current `DataType` and resets the layout.
+    pub fn open(&mut self, data: DataType) -> eyre::Result<()> {
+        self.layout = self.config.canvas.layout.clone();
+
+        self.replace(data)
+    }
+
+    /// Replaces the current `DataType` and keeps the layout.
+    pub fn replace(&mut self, mut data: DataType) -> eyre::Result<()> {
+        let (elems, action) = prepare_data(&self.config, &mut data)?;
+
+        self.ext.pool.reset();
+        self.ext.cache = FrameCache::new(*self.config.canvas.cache_mb as usize);
+        self.ext.data = Arc::new(data);
+        self.world = World::new(elems.clone());
+        self.elems = elems;
+        self.action = action;
+
+        self.canvas.offset = Vec2::default();
+
+        self.update_title();
+
+        Ok(())
+    }
+
+    fn open_next_target(&mut self) -> eyre::Result<()> {
+        self.open_target(1)
+    }
+
+    fn open_prev_target(&mut self) -> eyre::Result<()> {
+        self.open_target(-1)
+    }
+
+    // Skips targets which can not be opened, the index stays if none can.
+    fn open_target(&mut self, step: isize) -> eyre::Result<()> {
+        let old = self.playlist.index;
+
+        loop {
+            let path = match step {
+                1 => self.playlist.next(),
+                _ => self.playlist.prev(),
+            };
+            let Some(path) = path.map(|p| p.to_path_buf()) else {
+                break;
+            };
+
+            match self.read_target(&path).and_then(|data| self.open(data)) {
+                Ok(_) => return Ok(()),
+                Err(e) => tracing::error!(action = "open_target", path = ?path, error = %e),
+            }
+        }
+
+        self.playlist.index = old;
+        self.update_title();
+
+        Ok(())
+    }
+
+    fn read_target(&self, path: &Path) -> eyre::Result<DataType> {
+        if path == Path::new("-") {
+            return self
+                .env
+                .stdin
+                .clone()
+                .ok_or_eyre("stdin has already been read");
+        }
+
+        DataType::new(path)
+    }
+
+    // Next or previous volume in the parent folder, skips the ones which can not be opened.
+    fn open_sibling(&mut self, step: isize) -> eyre::Result<()> {
+        let Some(cur) = self.ext.data.path().map(|p| p.to_path_buf()) else {
+            return Ok(());
+        };
+        let list = archive::dir::get_siblings(&cur)?;
+        let Some(mut pos) = list.iter().position(|p| p.file_name() == cur.file_name()) else {
+            return Ok(());
+        };
+
+        while let Some(path) = pos.checked_add_signed(step).and_then(|pos| list.get(pos)) {
+            pos = pos.wrapping_add_signed(step);
+            tracing::info!(action = "open_sibling", path = ?path);
+
+            match DataType::new(path).and_then(|data| self.replace(data)) {
+                Ok(_) => {
+                    self.playlist.replace_current(path.clone());
+                    self.update_title();
+
+                    return Ok(());
+                }
+
+                Err(e) => tracing::error!(action = "open_sibling", path = ?path, error = %e),
+            }
+        }
+
+        tracing::info!("No more volumes");
+
+        Ok(())
+    }
+
+    fn update_title(&self) {
+        let mut title = "rmg".to_string();
+
+        if self.playlist.len() > 1 {
+            title.push(' ');
+            title.push_str(self.playlist.position().as_str());
+        }
+
+        // e.g. `frame 3/24`
+        if let Some((index, len)) = self.frame_index() {
+            if self.playback.paused {
+                title.push_str(format!(" - paused, frame {}/{len}", index + 1).as_str());
+            }
+        }
+
+        // e.g. `failed: 01.jpg: ERROR: Unknown Format`
+        if let Some((_, name, reason)) = self.failed_page() {
+            title.push_str(format!(" - failed: {name}: {reason}, press `r` to retry").as_str());
+        }
+
+        if let Action::Password { input, wrong } = &self.action {
+            if *wrong {
+                title.push_str(" - wrong password, try again:");
+            } else {
+                title.push_str(" - password:");
+            }
+
+            title.push(' ');
+            title.push_str("*".repeat(input.chars().count()).as_str());
+        }
+
+        self.window().set_title(title.as_str());
+    }
+
+    /// `(index, len)` of the first animation on screen.
+    pub fn frame_index(&self) -> Option<(usize, usize)> {
+        self.elems
+            .iter()
+            .filter(|page| page.state == State::Done)
+            .find_map(|page| page.frame.data.frame_index())
+    }
+
+    /// `(index, filename, reason)` of the first failed page on screen.
+    fn failed_page(&self) -> Option<(usize, String, &str)> {
+        let view = Rect::new_at_zero(self.canvas.size());
+
+        self.elems.iter().find_map(|page| {
+            let State::Failed(reason) = &page.state else {
+                return None;
+            };
+            if !view.is_hover(&page.cast_vertex) && !page.cast_vertex.is_hover(&view) {
+                return None;
+            }
+
+            let name = self.ext.data.file_name(page.file_index);
+
+            Some((page.index, name, reason.as_str()))
+        })
+    }
+
+    /// Returns `true` if the last page is fully scrolled into view, or its
+    /// placeholder if it failed.
+    fn is_end_of_target(&self) -> bool {
+        let Some(last) = self.elems.last() else {
+            return true;
+        };
+
+        match self.layout {
+            Layout::Vertical { .. } | Layout::Double { .. } => {
+                !last.frame.size.is_zero()
+                    && matches!(last.state, State::Done | State::Failed(_))
+                    && last.cast_vertex.max().y <= self.canvas.height()
+            }
+
+            _ => false,
+        }
+    }
+
+    fn move_down(&mut self) -> eyre::Result<()> {
+        if !self.is_end_of_target() {
+            self.env.flag_end = false;
+            self.canvas.move_down();
+
+            return Ok(());
+        }
+
+        // scroll past the end once more to continue
+        if !self.env.flag_end {
+            self.env.flag_end = true;
+            tracing::info!("End of target");
+
+            return Ok(());
+        }
+        self.env.flag_end = false;
+
+        // continue into the next target
+        if self.playlist.has_next() {
+            return self.open_next_target();
+        }
+
+        // continue into the next volume
+        self.open_sibling(1)
+    }
+
+    fn move_up(&mut self) {
+        self.env.flag_end = false;
+        self.canvas.move_up();
+    }
+
     fn run(&mut self, event_loop: EventLoop<()>) -> eyre::Result<()> {
         event_loop.run(move |event, elwt| {
             elwt.set_control_flow(ControlFlow::wait_duration(self.env.loop_dur));
@@ -155,14 +418,23 @@ impl App {
     }
 
     fn event_loop(&mut self, event: Event<()>, elwt: &ActiveEventLoop) -> eyre::Result<()> {
-        let window = self.window();
+        let id = self.window().id();
 
         match event {
             Event::AboutToWait => {
-                window.request_redraw();
+                self.on_watch()?;
+
+                self.window().request_redraw();
+
+                // wake up for the next animation frame
+                if let Some(deadline) = self.next_deadline() {
+                    if deadline < Instant::now() + self.env.loop_dur {
+                        elwt.set_control_flow(ControlFlow::WaitUntil(deadline));
+                    }
+                }
             }
 
-            Event::WindowEvent { window_id, event } if window_id == window.id() => {
+            Event::WindowEvent { window_id, event } if window_id == id => {
                 // dbg!(&event);
                 self.on_window_event(elwt, event)?;
             }
@@ -173,6 +445,18 @@ impl App {
         Ok(())
     }
 
+    // The earliest deadline of animated pages on screen.
+    fn next_deadline(&self) -> Option<Instant> {
+        let now = Instant::now();
+
+        self.elems
+            .iter()
+            .filter(|page| page.state == State::Done)
+            .filter_map(|page| page.frame.data.deadline())
+            .filter(|deadline| *deadline > now)
+            .min()
+    }
+
     fn flush(&mut self) -> eyre::Result<()> {
         let new_size = {
             let size = self.window().inner_size();
@@ -205,6 +489,12 @@ impl App {
 
         self.render()?;
 
+        let failed = self.failed_page().map(|(index, ..)| index);
+        if failed != self.env.last_failed {
+            self.env.last_failed = failed;
+            self.update_title();
+        }
+
         let mut buffer = self
             .surface
             .buffer_mut()
@@ -252,12 +542,171 @@ impl App {
 
             WindowEvent::CloseRequested => self.on_exit(elwt)?,
 
+            WindowEvent::DroppedFile(path) => self.on_dropped_file(path)?,
+
             _ => {}
         }
 
         Ok(())
     }
 
+    // Rescan the directory, keeps loaded pages and the scroll position.
+    fn on_watch(&mut self) -> eyre::Result<()> {
+        if !self.config.app.watch {
+            return Ok(());
+        }
+
+        if let Some(rx) = &self.env.scan {
+            match rx.try_recv() {
+                Ok(Some(data)) => self.apply_scan(data)?,
+
+                Ok(None) => {}
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => {}
+            }
+
+            self.env.scan = None;
+            self.env.last_scan = Instant::now();
+
+            return Ok(());
+        }
+
+        if self.env.last_scan.elapsed() < self.env.scan_dur {
+            return Ok(());
+        }
+
+        let DataType::Dir { path, filelist } = self.ext.data.as_ref() else {
+            return Ok(());
+        };
+        let (path, filelist) = (path.clone(), filelist.clone());
+
+        let (tx, rx) = mpsc::channel();
+        self.env.scan = Some(rx);
+
+        thread::spawn(move || {
+            // e.g. a file is being copied, try again later
+            let new_list = match archive::dir::get_list(&path) {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::debug!(action = "rescan", error = %e);
+                    let _ = tx.send(None);
+
+                    return;
+                }
+            };
+
+            // ignore the expanded pages of TIFF
+            if filelist
+                .iter()
+                .filter(|info| info.sub_index == 0)
+                .eq(new_list.iter())
+            {
+                let _ = tx.send(None);
+
+                return;
+            }
+
+            tracing::info!(
+                action = "rescan",
+                old = filelist.len(),
+                new = new_list.len()
+            );
+
+            let data = DataType::Dir {
+                path,
+                filelist: new_list,
+            }
+            .expand_pages();
+            let _ = tx.send(Some(data));
+        });
+
+        Ok(())
+    }
+
+    // Keeps the loaded pages of files which are still there.
+    fn apply_scan(&mut self, data: DataType) -> eyre::Result<()> {
+        let (Some(old_list), Some(new_list)) = (self.ext.data.filelist(), data.filelist()) else {
+            return Ok(());
+        };
+        let new_elems = data.gen_empty_pages(self.config.misc.padding_filename as usize)?;
+
+        // (file_index, sub_index) -> path
+        let old_paths: HashMap<(usize, usize), &str> = old_list
+            .iter()
+            .map(|info| ((info.index, info.sub_index), info.path.as_str()))
+            .collect();
+        let new_paths: HashMap<(usize, usize), &str> = new_list
+            .iter()
+            .map(|info| ((info.index, info.sub_index), info.path.as_str()))
+            .collect();
+
+        // (path, sub_index) -> page
+        let mut old_elems: HashMap<(&str, usize), Page> = HashMap::new();
+        for page in mem::take(&mut self.elems) {
+            if let Some(path) = old_paths.get(&(page.file_index, page.sub_index)) {
+                old_elems.insert((path, page.sub_index), page);
+            }
+        }
+
+        let mut elems = Vec::with_capacity(new_elems.len());
+        for page in new_elems.iter() {
+            let old = new_paths
+                .get(&(page.file_index, page.sub_index))
+                .and_then(|path| old_elems.remove(&(*path, page.sub_index)));
+
+            match old {
+                Some(mut old) => {
+                    old.index = page.index;
+                    old.file_index = page.file_index;
+
+                    elems.push(old);
+                }
+
+                None => elems.push(page.clone()),
+            }
+        }
+
+        // `file_index` may be changed
+        self.ext.cache.clear();
+        self.ext.pool.reset();
+        self.ext.data = Arc::new(data);
+        // empty pages, as in `replace()`
+        self.world = World::new(new_elems);
+        self.elems = elems;
+
+        Ok(())
+    }
+
+    fn on_dropped_file(&mut self, path: PathBuf) -> eyre::Result<()> {
+        tracing::info!(action = "drop", path = ?path);
+
+        let data = match DataType::new(&path) {
+            Ok(DataType::Unknown) => {
+                tracing::warn!("Unknown Format: {}", path.display());
+
+                return Ok(());
+            }
+
+            Ok(data) => data,
+
+            Err(e) => {
+                tracing::warn!("{e}: {}", path.display());
+
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = self.open(data) {
+            tracing::warn!("{e}: {}", path.display());
+
+            return Ok(());
+        }
+        self.playlist = Playlist::new(vec![path]);
+        self.update_title();
+
+        Ok(())
+    }
+
     fn on_cursor_moved(
         &mut self,
         sf: f64,
@@ -357,9 +806,9 @@ impl App {
 
             Layout::Vertical { .. } => {
                 if y < 0.0 {
-                    self.canvas.move_down();
+                    self.move_down()?;
                 } else if y > 0.0 {
-                    self.canvas.move_up();
+                    self.move_up();
                 }
             }
 
@@ -369,15 +818,18 @@ impl App {
         Ok(())
     }
 
-    fn on_keyboard(
-        &mut self,
-        KeyEvent {
+    fn on_keyboard(&mut self, event: KeyEvent, elwt: &ActiveEventLoop) -> eyre::Result<()> {
+        if let Action::Password { .. } = self.action {
+            return self.on_keyboard_password(event, elwt);
+        }
+
+        let KeyEvent {
             physical_key,
+            logical_key,
             state,
             ..
-        }: KeyEvent,
-        elwt: &ActiveEventLoop,
-    ) -> eyre::Result<()> {
+        } = event;
+
         if !state.is_pressed() {
             return Ok(());
         }
@@ -385,12 +837,12 @@ impl App {
         match physical_key {
             PhysicalKey::Code(KeyCode::KeyJ) | PhysicalKey::Code(KeyCode::ArrowDown) => {
                 // dbg!("move_down");
-                self.canvas.move_down();
+                self.move_down()?;
             }
 
             PhysicalKey::Code(KeyCode::KeyK) | PhysicalKey::Code(KeyCode::ArrowUp) => {
                 // dbg!("move_up");
-                self.canvas.move_up();
+                self.move_up();
             }
 
             PhysicalKey::Code(KeyCode::KeyH) | PhysicalKey::Code(KeyCode::ArrowLeft) => {
@@ -407,6 +859,53 @@ impl App {
 
             PhysicalKey::Code(KeyCode::KeyG) => self.on_keyboard_gesture()?,
 
+            PhysicalKey::Code(KeyCode::KeyN) => self.open_next_target()?,
+            PhysicalKey::Code(KeyCode::KeyP) => self.open_prev_target()?,
+
+            PhysicalKey::Code(KeyCode::BracketRight) => self.open_sibling(1)?,
+            PhysicalKey::Code(KeyCode::BracketLeft) => self.open_sibling(-1)?,
+
+            PhysicalKey::Code(KeyCode::KeyR) => self.on_keyboard_retry(),
+
+            PhysicalKey::Code(KeyCode::Space) => {
+                self.playback.paused = !self.playback.paused;
+                self.update_title();
+            }
+            PhysicalKey::Code(KeyCode::Period) => self.on_keyboard_step(1),
+            PhysicalKey::Code(KeyCode::Comma) => self.on_keyboard_step(-1),
+            PhysicalKey::Code(KeyCode::Equal) => {
+                self.playback.speed = (self.playback.speed * 2.0).min(8.0);
+            }
+            PhysicalKey::Code(KeyCode::Minus) => {
+                self.playback.speed = (self.playback.speed / 2.0).max(0.125);
+            }
+
+            // Aseprite
+            PhysicalKey::Code(
+                key @ (KeyCode::KeyT
+                | KeyCode::KeyS
+                | KeyCode::Digit1
+                | KeyCode::Digit2
+                | KeyCode::Digit3
+                | KeyCode::Digit4
+                | KeyCode::Digit5
+                | KeyCode::Digit6
+                | KeyCode::Digit7
+                | KeyCode::Digit8
+                | KeyCode::Digit9),
+            ) => self.on_keyboard_aseprite(key)?,
+
+            // `e` brighter, `E` darker
+            PhysicalKey::Code(KeyCode::KeyE) => {
+                let step = if logical_key.to_text() == Some("E") {
+                    -0.5
+                } else {
+                    0.5
+                };
+
+                self.on_keyboard_exposure(step)?
+            }
+
             PhysicalKey::Code(KeyCode::KeyQ) | PhysicalKey::Code(KeyCode::Escape) => {
                 self.on_exit(elwt)?
             }
@@ -419,6 +918,188 @@ impl App {
         Ok(())
     }
 
+    // Pauses and moves to the next or previous frame.
+    fn on_keyboard_step(&mut self, step: isize) {
+        self.playback.paused = true;
+
+        for page in self.elems.iter_mut() {
+            if page.state == State::Done {
+                page.frame.data.set_playback(self.playback);
+                page.frame.data.step(step);
+            }
+        }
+
+        self.update_title();
+    }
+
+    fn on_keyboard_exposure(&mut self, step: f32) -> eyre::Result<()> {
+        let exposure = frame::hdr::add_exposure(step);
+        tracing::info!(action = "exposure", value = exposure);
+
+        self.reload_pages(|fmt| fmt.is_hdr());
+
+        Ok(())
+    }
+
+    // Changes the first Aseprite file on screen.
+    fn on_keyboard_aseprite(&mut self, key: KeyCode) -> eyre::Result<()> {
+        let Some(page) = self
+            .elems
+            .iter_mut()
+            .find(|page| page.state == State::Done && page.frame.ase.is_some())
+        else {
+            return Ok(());
+        };
+        let Some(ase) = page.frame.ase.as_mut() else {
+            return Ok(());
+        };
+
+        match key {
+            KeyCode::KeyT => {
+                let tag = ase.next_tag();
+                tracing::info!(action = "aseprite_tag", tag = ?tag);
+            }
+
+            KeyCode::KeyS => {
+                let show = ase.toggle_slices();
+                tracing::info!(action = "aseprite_slices", show = show);
+            }
+
+            _ => {
+                // the first layer is the top one
+                let digits = [
+                    KeyCode::Digit1,
+                    KeyCode::Digit2,
+                    KeyCode::Digit3,
+                    KeyCode::Digit4,
+                    KeyCode::Digit5,
+                    KeyCode::Digit6,
+                    KeyCode::Digit7,
+                    KeyCode::Digit8,
+                    KeyCode::Digit9,
+                ];
+                let Some(nth) = digits.iter().position(|v| *v == key) else {
+                    return Ok(());
+                };
+
+                let res = ase.toggle_layer(nth);
+                tracing::info!(action = "aseprite_layer", layer = ?res);
+            }
+        }
+
+        // other sizes of the page are decoded with the old options
+        self.ext.cache.remove_file(page.file_index);
+
+        match self.layout {
+            // reloaded when the size is zero
+            Layout::Single { .. } => {
+                page.frame = Frame {
+                    ase: page.frame.ase.take(),
+                    ..Default::default()
+                };
+                page.state = State::Empty;
+            }
+
+            _ => page.free(),
+        }
+
+        Ok(())
+    }
+
+    // Loads failed pages again.
+    fn on_keyboard_retry(&mut self) {
+        let mut nums = 0;
+        for page in self.elems.iter_mut() {
+            if let State::Failed(_) = page.state {
+                page.retry();
+                nums += 1;
+            }
+        }
+
+        tracing::info!(action = "retry", pages = nums);
+    }
+
+    // Decodes pages of `fmt` again.
+    fn reload_pages(&mut self, f: impl Fn(&FrameFmt) -> bool) {
+        self.ext.cache.retain(|frame| !f(&frame.fmt));
+
+        for page in self.elems.iter_mut() {
+            if !f(&page.frame.fmt) {
+                continue;
+            }
+
+            match self.layout {
+                // reloaded when the size is zero
+                Layout::Single { .. } => {
+                    page.frame = Frame::default();
+                    page.state = State::Empty;
+                }
+
+                _ => page.free(),
+            }
+        }
+    }
+
+    fn on_keyboard_password(
+        &mut self,
+        KeyEvent {
+            physical_key,
+            state,
+            text,
+            ..
+        }: KeyEvent,
+        elwt: &ActiveEventLoop,
+    ) -> eyre::Result<()> {
+        if !state.is_pressed() {
+            return Ok(());
+        }
+
+        let Action::Password { input, wrong } = &mut self.action else {
+            unreachable!()
+        };
+
+        match physical_key {
+            PhysicalKey::Code(KeyCode::Escape) => return self.on_exit(elwt),
+
+            PhysicalKey::Code(KeyCode::Backspace) => {
+                input.pop();
+            }
+
+            PhysicalKey::Code(KeyCode::Enter) | PhysicalKey::Code(KeyCode::NumpadEnter) => {
+                let password = mem::take(input);
+
+                // jobs in the pool may still hold the old one
+                let mut data = self.ext.data.as_ref().clone();
+                data.set_password(Some(password));
+
+                match data.need_password() {
+                    Ok(false) => self.action = Action::View,
+
+                    Ok(true) => {
+                        tracing::info!("{}", ArchiveError::WrongPassword);
+                        *wrong = true;
+                    }
+
+                    Err(e) => {
+                        tracing::error!(action = "password", error = %e);
+                        *wrong = true;
+                    }
+                }
+                self.ext.data = Arc::new(data);
+            }
+
+            _ => {
+                if let Some(text) = text {
+                    input.extend(text.chars().filter(|c| !c.is_control()));
+                }
+            }
+        }
+
+        self.update_title();
+
+        Ok(())
+    }
+
     fn on_exit(&mut self, elwt: &ActiveEventLoop) -> eyre::Result<()> {
         tracing::info!("exit");
 
@@ -476,14 +1157,23 @@ impl App {
 
             self.env.flag_fullscreen = false;
         } else {
-            let mut modes = self.monitor.as_ref().unwrap().video_modes();
-            let first = modes.nth(0).unwrap();
+            let mode = self
+                .monitor
+                .as_ref()
+                .and_then(|monitor| monitor.video_modes().next());
+
+            tracing::debug!("VideoMode = {:?}", &mode);
 
-            tracing::debug!("VideoMode = {:?}", &first);
+            // borderless on the current monitor if there is no video mode
+            let fullscreen = match mode {
+                Some(mode) => winit::window::Fullscreen::Exclusive(mode),
+                None => {
+                    tracing::warn!("no video mode, use borderless fullscreen");
 
-            // window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)));
-            self.window()
-                .set_fullscreen(Some(winit::window::Fullscreen::Exclusive(first)));
+                    winit::window::Fullscreen::Borderless(None)
+                }
+            };
+            self.window().set_fullscreen(Some(fullscreen));
 
             self.env.flag_fullscreen = true;
         }
@@ -494,14 +1184,43 @@ impl App {
     }
 }
 
+// Returns empty pages and the first action.
+fn prepare_data(config: &Config, data: &mut DataType) -> eyre::Result<(Vec<Page>, Action)> {
+    data.set_password(config.app.password.clone());
+
+    let elems = data.gen_empty_pages(config.misc.padding_filename as usize)?;
+    let action = {
+        if data.need_password()? {
+            Action::Password {
+                input: String::new(),
+                // the password from config is wrong
+                wrong: config.app.password.is_some(),
+            }
+        } else {
+            Action::View
+        }
+    };
+
+    Ok((elems, action))
+}
+
 impl EnvData {
     fn new() -> Self {
         Self {
             flag_gesture: false,
             flag_fullscreen: false,
+            flag_end: false,
 
             // 90FPS
             loop_dur: Duration::from_millis(1000 / 90),
+
+            last_scan: Instant::now(),
+            scan_dur: Duration::from_secs(1),
+
+            last_failed: None,
+
+            scan: None,
+            stdin: None,
         }
     }
 }
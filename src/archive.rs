@@ -1,5 +1,6 @@
 pub mod dir;
 pub mod file;
+pub mod list;
 pub mod zip;
 
 // feature
@@ -9,12 +10,18 @@ use crate::*;
 
 // ==============================================
 use infer;
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+};
 
+#[derive(Clone)]
 pub enum DataType {
     Archive {
         path: PathBuf,
         fmt: ArchiveFmt,
         filelist: FileList,
+        password: Option<String>,
     },
 
     Dir {
@@ -26,25 +33,60 @@ pub enum DataType {
         path: PathBuf,
     },
 
+    /// Read from stdin, e.g. `cat a.zip | rmg -`
+    Blob {
+        // `None` means single image.
+        fmt: Option<ArchiveFmt>,
+        filelist: FileList,
+        // shared by the clones, e.g. the stdin copy for the playlist
+        blob: Arc<[u8]>,
+        password: Option<String>,
+    },
+
+    /// Arbitrary files from different places, e.g. `rmg --list list.txt`
+    List {
+        filelist: FileList,
+    },
+
     Unknown,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FileList {
     pub inner: Vec<FileInfo>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FileInfo {
     pub path: String,
     pub index: usize,
+    /// page of multi-page formats, e.g. TIFF
+    pub sub_index: usize,
 }
 
+#[derive(Clone)]
 pub enum ArchiveFmt {
     Zip,
     Tar,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveError {
+    PasswordRequired,
+    WrongPassword,
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PasswordRequired => write!(f, "The archive is encrypted, password required"),
+            Self::WrongPassword => write!(f, "Wrong password"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
 impl FileList {
     pub fn gen_empty_pages(&self, fname_padding: usize) -> Vec<Page> {
         let mut tmp = Vec::with_capacity(self.len());
@@ -71,20 +113,44 @@ impl FileList {
 
         let mut res = vec![];
         for (index, (info, ..)) in tmp.iter().enumerate() {
-            res.push(Page::new_empty(index))
+            let mut page = Page::new_empty(index, info.index);
+            page.sub_index = info.sub_index;
+
+            res.push(page)
         }
 
         res
     }
+
+    // `nums[i]` is the number of pages of `inner[i]`.
+    pub fn expand(&mut self, nums: &[usize]) {
+        let mut res = Vec::with_capacity(nums.iter().sum());
+
+        for (info, &n) in mem::take(&mut self.inner).into_iter().zip(nums) {
+            for sub_index in 0..n.max(1) {
+                res.push(FileInfo {
+                    sub_index,
+                    ..info.clone()
+                });
+            }
+        }
+
+        self.inner = res;
+    }
 }
 
 impl DataType {
     pub fn new(path: &Path) -> eyre::Result<Self> {
+        if path == Path::new("-") {
+            return Self::from_bytes(archive::file::read_stdin()?);
+        }
+
         if path.is_dir() {
             return Ok(Self::Dir {
                 filelist: archive::dir::get_list(path)?,
                 path: path.to_path_buf(),
-            });
+            }
+            .expand_pages());
         }
 
         // FIXME: remove space in the path
@@ -101,16 +167,23 @@ impl DataType {
         let ext = t.extension();
 
         Ok(match ext {
+            // KRA is a zip file
+            "zip" if frame::kra::is_kra(&archive::file::read_head(path, 64)?) => Self::SingleImg {
+                path: path.to_path_buf(),
+            },
+
             "zip" => Self::Archive {
                 fmt: ArchiveFmt::Zip,
                 filelist: archive::zip::get_list(path)?,
                 path: path.to_path_buf(),
+                password: None,
             },
 
             "tar" => Self::Archive {
                 fmt: ArchiveFmt::Tar,
                 filelist: archive::tar::get_list(path)?,
                 path: path.to_path_buf(),
+                password: None,
             },
 
             _ if SUPPORTED_FORMAT.contains(&ext) => Self::SingleImg {
@@ -118,12 +191,136 @@ impl DataType {
             },
 
             _ => Self::Unknown,
-        })
+        }
+        .expand_pages())
+    }
+
+    pub fn from_bytes(blob: Vec<u8>) -> eyre::Result<Self> {
+        let ext = infer::get(&blob).map(|t| t.extension());
+
+        let (fmt, filelist) = {
+            match ext {
+                Some("zip") if frame::kra::is_kra(&blob) => (None, FileList::new()),
+                Some("zip") => (
+                    Some(ArchiveFmt::Zip),
+                    archive::zip::get_list_from_bytes(&blob)?,
+                ),
+                Some("tar") => (
+                    Some(ArchiveFmt::Tar),
+                    archive::tar::get_list_from_bytes(&blob)?,
+                ),
+
+                // Not archive.
+                _ => (None, FileList::new()),
+            }
+        };
+
+        Ok(Self::Blob {
+            fmt,
+            filelist,
+            blob: Arc::from(blob),
+            password: None,
+        }
+        .expand_pages())
+    }
+
+    pub fn from_list(path: &Path) -> eyre::Result<Self> {
+        Ok(Self::List {
+            filelist: archive::list::get_list(path)?,
+        }
+        .expand_pages())
+    }
+
+    // One `FileInfo` per page for multi-page TIFF.
+    pub fn expand_pages(mut self) -> Self {
+        let Some(filelist) = self.filelist() else {
+            return self;
+        };
+
+        let nums: Vec<usize> = filelist
+            .iter()
+            .map(|info| {
+                if !info.is_tiff() {
+                    return 1;
+                }
+
+                match &self {
+                    // only the IFD chain is read
+                    Self::Dir { .. } | Self::List { .. } => File::open(info.path.as_str())
+                        .map_err(eyre::Report::from)
+                        .and_then(|file| frame::tiff::page_nums(BufReader::new(file))),
+
+                    _ => self
+                        .get_file(info.index)
+                        .and_then(|blob| frame::tiff::page_nums(Cursor::new(blob))),
+                }
+                .unwrap_or(1)
+            })
+            .collect();
+
+        if let Some(filelist) = self.filelist_mut() {
+            filelist.expand(&nums);
+        }
+
+        self
+    }
+
+    pub fn filelist(&self) -> Option<&FileList> {
+        match self {
+            Self::Archive { filelist, .. }
+            | Self::Dir { filelist, .. }
+            | Self::Blob {
+                fmt: Some(_),
+                filelist,
+                ..
+            }
+            | Self::List { filelist } => Some(filelist),
+
+            _ => None,
+        }
+    }
+
+    fn filelist_mut(&mut self) -> Option<&mut FileList> {
+        match self {
+            Self::Archive { filelist, .. }
+            | Self::Dir { filelist, .. }
+            | Self::Blob {
+                fmt: Some(_),
+                filelist,
+                ..
+            }
+            | Self::List { filelist } => Some(filelist),
+
+            _ => None,
+        }
+    }
+
+    /// Path of the file at `index`, or of the target if it is not a list of files.
+    pub fn file_name(&self, index: usize) -> String {
+        self.filelist()
+            .and_then(|list| list.get_by_index(index))
+            .map(|info| info.path.clone())
+            .or_else(|| self.path().map(|p| p.display().to_string()))
+            .unwrap_or_default()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Archive { path, .. } | Self::Dir { path, .. } | Self::SingleImg { path } => {
+                Some(path.as_path())
+            }
+
+            _ => None,
+        }
     }
 
     pub fn file_nums(&self) -> usize {
         match self {
-            DataType::Archive { filelist, .. } | DataType::Dir { filelist, .. } => filelist.len(),
+            DataType::Archive { filelist, .. }
+            | DataType::Dir { filelist, .. }
+            | DataType::List { filelist } => filelist.len(),
+            DataType::Blob { fmt: None, .. } => 1,
+            DataType::Blob { filelist, .. } => filelist.len(),
             DataType::SingleImg { .. } => 1,
             _ => unreachable!(),
         }
@@ -135,24 +332,104 @@ impl DataType {
                 filelist.gen_empty_pages(fname_padding)
             }
 
-            Self::SingleImg { path } => vec![Page::new_empty(0)],
+            Self::SingleImg { .. } | Self::Blob { fmt: None, .. } => {
+                let page_nums = |blob: &[u8]| match imagesize::image_type(blob) {
+                    Ok(imagesize::ImageType::Tiff) => frame::tiff::page_nums(Cursor::new(blob)),
+                    _ => Ok(1),
+                };
+                let nums = match self {
+                    Self::Blob { blob, .. } => page_nums(blob)?,
+                    _ => page_nums(&self.get_file(0)?)?,
+                };
+
+                (0..nums)
+                    .map(|sub_index| {
+                        let mut page = Page::new_empty(sub_index, 0);
+                        page.sub_index = sub_index;
+
+                        page
+                    })
+                    .collect()
+            }
+
+            Self::Blob { filelist, .. } => filelist.gen_empty_pages(fname_padding),
+
+            // keep the order of the list
+            Self::List { filelist } => filelist
+                .iter()
+                .filter(|info| info.is_supported())
+                .enumerate()
+                .map(|(index, info)| {
+                    let mut page = Page::new_empty(index, info.index);
+                    page.sub_index = info.sub_index;
+
+                    page
+                })
+                .collect(),
 
             Self::Unknown => eyre::bail!("Unknown Format"),
         })
     }
 
+    /// Returns `true` if the archive is encrypted and no valid password is set.
+    pub fn need_password(&self) -> eyre::Result<bool> {
+        match self {
+            Self::Archive {
+                path,
+                fmt: ArchiveFmt::Zip,
+                password,
+                ..
+            } => archive::zip::need_password(path, password.as_deref()),
+
+            Self::Blob {
+                fmt: Some(ArchiveFmt::Zip),
+                blob,
+                password,
+                ..
+            } => archive::zip::need_password_from_bytes(blob, password.as_deref()),
+
+            _ => Ok(false),
+        }
+    }
+
+    pub fn set_password(&mut self, value: Option<String>) {
+        if let Self::Archive { password, .. } | Self::Blob { password, .. } = self {
+            *password = value;
+        }
+    }
+
     pub fn get_file(&self, index: usize) -> eyre::Result<Vec<u8>> {
         match &self {
-            Self::Archive { path, fmt, .. } => match fmt {
-                ArchiveFmt::Zip => archive::zip::get_file(path, index),
+            Self::Archive {
+                path,
+                fmt,
+                password,
+                ..
+            } => match fmt {
+                ArchiveFmt::Zip => archive::zip::get_file(path, index, password.as_deref()),
                 ArchiveFmt::Tar => archive::tar::get_file(path, index),
                 _ => unreachable!(),
             },
 
-            Self::Dir { path, .. } => archive::dir::get_file(path, index),
+            Self::Dir { filelist, .. } => archive::dir::get_file(filelist, index),
 
             Self::SingleImg { path } => archive::file::get_file(path),
 
+            Self::Blob {
+                fmt,
+                blob,
+                password,
+                ..
+            } => match fmt {
+                Some(ArchiveFmt::Zip) => {
+                    archive::zip::get_file_from_bytes(blob, index, password.as_deref())
+                }
+                Some(ArchiveFmt::Tar) => archive::tar::get_file_from_bytes(blob, index),
+                None => Ok(blob.to_vec()),
+            },
+
+            Self::List { filelist } => archive::list::get_file(filelist, index),
+
             Self::Unknown => eyre::bail!("Unknown URI"),
         }
     }
@@ -174,11 +451,26 @@ impl FileList {
     pub fn len(&self) -> usize {
         self.inner.len()
     }
+
+    /// Find by `FileInfo.index` rather than the position in the list.
+    pub fn get_by_index(&self, index: usize) -> Option<&FileInfo> {
+        self.inner.iter().find(|info| info.index == index)
+    }
 }
 
 impl FileInfo {
     pub fn new(path: String, index: usize) -> Self {
-        Self { path, index }
+        Self {
+            path,
+            index,
+            sub_index: 0,
+        }
+    }
+
+    pub fn is_tiff(&self) -> bool {
+        let path = self.path.to_lowercase();
+
+        path.ends_with(".tif") || path.ends_with(".tiff")
     }
 
     pub fn is_supported(&self) -> bool {
@@ -1,48 +1,14 @@
-use crate::archive::*;
-use std::{fs::OpenOptions, io::Read, path::Path};
+use crate::{archive::*, natural_cmp, ARCHIVE_FORMAT};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-pub fn get_file<P>(path: &P, index: usize) -> eyre::Result<Vec<u8>>
-where
-    P: AsRef<Path> + ?Sized,
-{
-    let mut pos = 0;
-    for file in WalkDir::new(path).contents_first(true).into_iter() {
-        let file = file?;
-
-        // if !file.file_type().is_file() {
-        //     continue;
-        // }
-
-        if pos == index {
-            let mut file = OpenOptions::new()
-                .read(true)
-                .write(false)
-                .create(false)
-                .open(file.path())?;
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer)?;
-
-            // tracing::debug!(
-            //     "get_file():
-            //  pos = {}
-            //  index = {}",
-            //     pos,
-            //     index
-            // );
-
-            return Ok(buffer);
-        } else {
-            pos += 1;
-        }
-    }
+// By the path from `get_list()`, new files in the directory don't shift the index.
+pub fn get_file(filelist: &FileList, index: usize) -> eyre::Result<Vec<u8>> {
+    let Some(info) = filelist.get_by_index(index) else {
+        eyre::bail!("ERROR: Not found file with index `{index}` in dir")
+    };
 
-    eyre::bail!(format!(
-        "
-ERROR: Not found file with index `{index}` in dir `{}`
-",
-        path.as_ref().display(),
-    ))
+    archive::file::get_file(info.path.as_str())
 }
 
 pub fn get_list<P>(path: &P) -> eyre::Result<FileList>
@@ -61,3 +27,38 @@ where
 
     Ok(res)
 }
+
+/// Archives and subdirectories next to `path`, in natural order.
+pub fn get_siblings<P>(path: &P) -> eyre::Result<Vec<PathBuf>>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    let Some(parent) = path.as_ref().parent() else {
+        return Ok(vec![]);
+    };
+    // e.g. `rmg vol01.cbz`
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+
+    let mut res = vec![];
+    for file in std::fs::read_dir(parent)? {
+        let path = file?.path();
+
+        let is_archive = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ARCHIVE_FORMAT.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false);
+
+        if path.is_dir() || is_archive {
+            res.push(path);
+        }
+    }
+
+    res.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+
+    Ok(res)
+}
@@ -1,5 +1,9 @@
 use crate::*;
-use std::{fs::OpenOptions, io::Read, path::Path};
+use std::{
+    fs::OpenOptions,
+    io::{self, Read},
+    path::Path,
+};
 
 pub fn get_file<P>(path: &P) -> eyre::Result<Vec<u8>>
 where
@@ -17,9 +21,29 @@ where
     Ok(res)
 }
 
+// Reads the first `len` bytes, e.g. for detecting the file type.
+pub fn read_head<P>(path: &P, len: u64) -> eyre::Result<Vec<u8>>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    let file = OpenOptions::new().read(true).open(path)?;
+
+    let mut res = Vec::new();
+    file.take(len).read_to_end(&mut res)?;
+
+    Ok(res)
+}
+
 pub fn get_list(path: &Path) -> eyre::Result<FileList> {
     let mut res = FileList::new();
     res.push(FileInfo::new(path.display().to_string(), 0));
 
     Ok(res)
 }
+
+pub fn read_stdin() -> eyre::Result<Vec<u8>> {
+    let mut res = Vec::new();
+    io::stdin().lock().read_to_end(&mut res)?;
+
+    Ok(res)
+}
@@ -0,0 +1,55 @@
+use crate::*;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+// Newline-separated paths, from a file or `-` (stdin).
+//
+// e.g.
+//     /tmp/a.jpg
+//     ./b.png
+pub fn get_list<P>(path: &P) -> eyre::Result<FileList>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    if path.as_ref() == Path::new("-") {
+        let stdin = io::stdin();
+
+        return read_list(stdin.lock());
+    }
+
+    let file = File::open(path.as_ref())?;
+
+    read_list(BufReader::new(file))
+}
+
+fn read_list<R>(render: R) -> eyre::Result<FileList>
+where
+    R: BufRead,
+{
+    let mut res = FileList::new();
+
+    for line in render.lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let index = res.len();
+        res.push(FileInfo::new(line.to_string(), index));
+    }
+
+    Ok(res)
+}
+
+pub fn get_file(filelist: &FileList, index: usize) -> eyre::Result<Vec<u8>> {
+    let Some(info) = filelist.get_by_index(index) else {
+        eyre::bail!("ERROR: Not found file with index `{index}` in list")
+    };
+
+    archive::file::get_file(info.path.as_str())
+}
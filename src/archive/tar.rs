@@ -24,6 +24,24 @@ where
     eyre::bail!("")
 }
 
+pub fn get_file_from_bytes(bytes: &[u8], index: usize) -> eyre::Result<Vec<u8>> {
+    #[cfg(feature = "ex_tar")]
+    {
+        return feat::read_file(bytes, index);
+    }
+
+    eyre::bail!("")
+}
+
+pub fn get_list_from_bytes(bytes: &[u8]) -> eyre::Result<FileList> {
+    #[cfg(feature = "ex_tar")]
+    {
+        return feat::read_list(bytes);
+    }
+
+    eyre::bail!("")
+}
+
 #[cfg(feature = "ex_tar")]
 mod feat {
     use crate::archive::*;
@@ -34,16 +52,20 @@ mod feat {
     where
         P: AsRef<Path> + ?Sized,
     {
-        let mut tar = {
-            let file = OpenOptions::new()
-                .read(true)
-                .write(false)
-                .create(false)
-                .open(path)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(false)
+            .create(false)
+            .open(path)?;
 
-            tar::Archive::new(file)
-        };
+        read_list(file)
+    }
 
+    pub fn read_list<R>(render: R) -> eyre::Result<FileList>
+    where
+        R: Read,
+    {
+        let mut tar = tar::Archive::new(render);
         let mut res = FileList::new();
 
         for (index, file) in tar.entries()?.enumerate() {
@@ -67,7 +89,15 @@ mod feat {
             .read(true)
             .create(false)
             .open(tar_file)?;
-        let mut tar_file = tar::Archive::new(&file);
+
+        read_file(file, index)
+    }
+
+    pub fn read_file<R>(render: R, index: usize) -> eyre::Result<Vec<u8>>
+    where
+        R: Read,
+    {
+        let mut tar_file = tar::Archive::new(render);
         let mut buffer = vec![];
 
         for (n, file) in tar_file.entries()?.enumerate() {
@@ -1,39 +1,153 @@
 use crate::*;
 
-use ::zip::ZipArchive;
+use ::zip::{result::ZipError, ZipArchive};
 use std::{
     fs::File,
-    io::{prelude::*, BufReader},
+    io::{self, prelude::*, BufReader, Cursor},
     path::Path,
 };
 
-pub fn get_file<P>(path: &P, index: usize) -> eyre::Result<Vec<u8>>
+pub fn get_file<P>(path: &P, index: usize, password: Option<&str>) -> eyre::Result<Vec<u8>>
 where
     P: AsRef<Path> + ?Sized,
 {
     let file = File::open(path.as_ref())?;
     let render = BufReader::new(file);
+
+    read_file(render, index, password)
+}
+
+pub fn get_file_from_bytes(
+    bytes: &[u8],
+    index: usize,
+    password: Option<&str>,
+) -> eyre::Result<Vec<u8>> {
+    read_file(Cursor::new(bytes), index, password)
+}
+
+fn read_file<R>(render: R, index: usize, password: Option<&str>) -> eyre::Result<Vec<u8>>
+where
+    R: Read + Seek,
+{
     let mut zip = ZipArchive::new(render)?;
-    let mut file = zip.by_index(index)?;
+
+    let res = {
+        match password {
+            Some(pw) => zip.by_index_decrypt(index, pw.as_bytes()),
+            None => zip.by_index(index),
+        }
+    };
+    let mut file = match res {
+        Ok(file) => file,
+
+        Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)) => {
+            return Err(ArchiveError::PasswordRequired.into())
+        }
+        Err(ZipError::InvalidPassword) => return Err(ArchiveError::WrongPassword.into()),
+        Err(e) => return Err(e.into()),
+    };
 
     let mut res = Vec::with_capacity(file.size() as usize);
-    file.read_to_end(&mut res)?;
+    if let Err(e) = file.read_to_end(&mut res) {
+        if is_invalid_password(&e) {
+            return Err(ArchiveError::WrongPassword.into());
+        }
+
+        return Err(e.into());
+    }
 
     Ok(res)
 }
 
-pub fn get_list<P>(path: &P) -> eyre::Result<FileList>
+// `ZipError::InvalidPassword` wrapped by `io::Error`.
+fn is_invalid_password(e: &io::Error) -> bool {
+    matches!(
+        e.get_ref().and_then(|e| e.downcast_ref::<ZipError>()),
+        Some(ZipError::InvalidPassword)
+    )
+}
+
+/// Returns `true` if the archive is encrypted and `password` is missing or wrong.
+pub fn need_password<P>(path: &P, password: Option<&str>) -> eyre::Result<bool>
 where
     P: AsRef<Path> + ?Sized,
 {
-    let mut res = FileList::new();
+    let file = File::open(path.as_ref())?;
+    let render = BufReader::new(file);
+
+    check_password(render, password)
+}
+
+pub fn need_password_from_bytes(bytes: &[u8], password: Option<&str>) -> eyre::Result<bool> {
+    check_password(Cursor::new(bytes), password)
+}
 
+// Entries are encrypted with the same password, so only the first file is checked.
+fn check_password<R>(render: R, password: Option<&str>) -> eyre::Result<bool>
+where
+    R: Read + Seek,
+{
+    let mut zip = ZipArchive::new(render)?;
+
+    let mut first = None;
+    for index in 0..zip.len() {
+        if zip.by_index_raw(index)?.is_file() {
+            first = Some(index);
+
+            break;
+        }
+    }
+    let Some(index) = first else {
+        return Ok(false);
+    };
+
+    match zip.by_index(index) {
+        Ok(_) => return Ok(false),
+        Err(ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED)) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let Some(pw) = password else {
+        return Ok(true);
+    };
+    let mut file = match zip.by_index_decrypt(index, pw.as_bytes()) {
+        Ok(file) => file,
+        Err(ZipError::InvalidPassword) => return Ok(true),
+        Err(e) => return Err(e.into()),
+    };
+
+    // ZipCrypto only checks 1 byte of the header, so a wrong password
+    // usually passes `by_index_decrypt()` and fails on the CRC32 check.
+    match file.read_to_end(&mut Vec::new()) {
+        Ok(_) => Ok(false),
+        Err(_) => Ok(true),
+    }
+}
+
+pub fn get_list<P>(path: &P) -> eyre::Result<FileList>
+where
+    P: AsRef<Path> + ?Sized,
+{
     let file = File::open(path.as_ref())?;
     let render = BufReader::new(file);
+
+    read_list(render)
+}
+
+pub fn get_list_from_bytes(bytes: &[u8]) -> eyre::Result<FileList> {
+    read_list(Cursor::new(bytes))
+}
+
+fn read_list<R>(render: R) -> eyre::Result<FileList>
+where
+    R: Read + Seek,
+{
+    let mut res = FileList::new();
     let mut zip = ZipArchive::new(render)?;
 
     for index in 0..zip.len() {
-        let file = zip.by_index(index)?;
+        // Encrypted entries can not be opened without password.
+        let file = zip.by_index_raw(index)?;
 
         res.push(FileInfo::new(file.name().to_string(), index));
     }
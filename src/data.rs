@@ -1,3 +1,4 @@
 pub mod config;
 pub mod history;
 pub mod notes;
+pub mod playlist;
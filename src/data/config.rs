@@ -18,6 +18,7 @@ pub struct Config {
     pub layout_double: ConfLayoutDouble,
 
     pub on_scroll: ConfOnScroll,
+    pub aseprite: ConfAseprite,
 
     pub once: ConfOnce,
     // TODO: MouseMap
@@ -32,6 +33,11 @@ pub struct ConfOnce {
 #[derive(Debug, Default, Clone, EsynDe)]
 pub struct ConfApp {
     pub target: PathBuf,
+    pub targets: Vec<PathBuf>,
+    pub password: Option<String>,
+    pub list: Option<PathBuf>,
+    /// rescan the directory for new pages.
+    pub watch: bool,
 }
 
 #[derive(Debug, Default, Clone, EsynDe)]
@@ -46,6 +52,11 @@ pub struct ConfCanvas {
 
     pub bg: u32,
     pub cache_limit: u32,
+    /// pages decoded ahead in the reading direction, half of them behind.
+    /// vertical and double layouts only
+    pub pre_load_nums: u32,
+    /// decoded pages kept after leaving the view, in MB
+    pub cache_mb: CacheMb,
 
     // unused
     pub font_path: Option<PathBuf>,
@@ -56,6 +67,17 @@ pub struct ConfPage {
     pub size: Size,
     pub img_resize_algo: WrapResizeAlg,
     pub anim_resize_algo: WrapResizeAlg,
+    /// in MB, larger animations are decoded on the fly.
+    pub anim_mem_limit: AnimMemMb,
+
+    /// for HDR and EXR
+    pub tone_map: ToneMap,
+    /// in stops
+    pub exposure: f32,
+
+    /// convert embedded ICC profiles into `icc_output`, or sRGB.
+    pub color_management: TrueByDefault,
+    pub icc_output: Option<PathBuf>,
 }
 
 #[derive(Debug, Default, Clone, EsynDe)]
@@ -81,6 +103,71 @@ pub struct ConfOnScroll {
     pub step_y: f32,
 }
 
+#[derive(Debug, Default, Clone, EsynDe)]
+pub struct ConfAseprite {
+    /// play only this tag
+    pub tag: Option<String>,
+    pub hidden_layers: Vec<String>,
+    pub show_slices: bool,
+}
+
+// Values which are not `esyn_default()` if missing in the config, e.g. an old
+// config file.
+macro_rules! impl_conf_default {
+    ( $( $(#[$attr:meta])* $name:ident($t:ty) = $v:expr; )* ) => {$(
+$(#[$attr])*
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct $name(pub $t);
+
+impl Default for $name {
+    fn default() -> Self {
+        Self($v)
+    }
+}
+
+impl std::ops::Deref for $name {
+    type Target = $t;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl esyn::EsynDefault for $name {
+    fn esyn_default() -> Self {
+        Self::default()
+    }
+}
+
+impl esyn::DeRs<esyn::Expr> for $name {
+    fn de(ast: &esyn::Expr) -> esyn::Res<Self> {
+        Ok(Self(<$t as esyn::DeRs<esyn::Expr>>::de(ast)?))
+    }
+}
+
+impl esyn::MutPath for $name {
+    fn mut_path(
+        &mut self,
+        _iter: &mut std::slice::Iter<&esyn::syn::Ident>,
+        ast: &esyn::Expr,
+    ) -> esyn::Res<()> {
+        *self = <Self as esyn::DeRs<esyn::Expr>>::de(ast)?;
+
+        Ok(())
+    }
+}
+    )*};
+}
+
+impl_conf_default! {
+    /// `true` if missing.
+    TrueByDefault(bool) = true;
+    /// 256 MB if missing.
+    CacheMb(u32) = 256;
+    /// 256 MB if missing.
+    AnimMemMb(u32) = 256;
+}
+
 impl Config {
     pub fn new() -> eyre::Result<Self> {
         Self::from_str(DEFAULT_CONFIG)
@@ -139,6 +226,17 @@ impl Config {
             .get::<ConfOnScroll>(&esyn)?
             .get();
 
+        // missing in old config files
+        let aseprite = match EsynBuilder::new()
+            .set_fn("aseprite")
+            .flag_res()
+            .get::<ConfAseprite>(&esyn)
+        {
+            Ok(v) => v.get(),
+            Err(esyn::MyErr::NotFound(_)) => ConfAseprite::default(),
+            Err(e) => return Err(e.into()),
+        };
+
         let once = EsynBuilder::new()
             .set_fn("once")
             .flag_res()
@@ -155,6 +253,7 @@ impl Config {
             gestures,
             layout_double,
             on_scroll,
+            aseprite,
         })
     }
 
@@ -197,6 +296,11 @@ impl Config {
     }
 
     pub fn update_env(&mut self) -> eyre::Result<()> {
+        // ConfApp
+        if let Ok(v) = std::env::var("RMG_PASSWORD") {
+            self.app.password = Some(v);
+        }
+
         Ok(())
     }
 
@@ -216,6 +320,12 @@ impl Config {
             let (w, h) = v.split_once('x').unwrap();
             self.page.size = Size::new(w.parse().unwrap(), h.parse().unwrap());
         }
+        if let Some(v) = args.opt_value_from_str::<_, u32>("--pre-load-nums")? {
+            self.canvas.pre_load_nums = v;
+        }
+        if let Some(v) = args.opt_value_from_str::<_, u32>("--cache-mb")? {
+            self.canvas.cache_mb = CacheMb(v);
+        }
         if let Some(v) = args.opt_value_from_str::<_, String>("--layout")? {
             self.canvas.layout = {
                 match v.to_uppercase().as_str() {
@@ -226,7 +336,7 @@ impl Config {
                         align: Default::default(),
                         gap: Gap { x: 5.0, y: 10.0 },
                     },
-                    "H" | "SINGLE" => Layout::Horizontal { align: Align::Left },
+                    "H" | "HORIZONTAL" => Layout::Horizontal { align: Align::Left },
                     "S" | "SINGLE" => Layout::Single {
                         mouse_pos: Vec2::default(),
                         flag_scroll: false,
@@ -236,11 +346,55 @@ impl Config {
                         max_zoom: 40,
                     },
 
-                    _ => unimplemented!(),
+                    _ => eyre::bail!("unknown layout: {v}"),
                 }
             };
         }
 
+        // ConfPage
+        if let Some(v) = args.opt_value_from_str::<_, String>("--tone-map")? {
+            self.page.tone_map = {
+                match v.to_uppercase().as_str() {
+                    "ACES" => ToneMap::Aces,
+                    "REINHARD" => ToneMap::Reinhard,
+                    "EXPOSURE" => ToneMap::Exposure,
+
+                    _ => eyre::bail!("unknown tone map: {v}"),
+                }
+            };
+        }
+        if let Some(v) = args.opt_value_from_str::<_, f32>("--exposure")? {
+            self.page.exposure = v;
+        }
+        if let Some(v) = args.opt_value_from_str::<_, bool>("--color-management")? {
+            self.page.color_management = TrueByDefault(v);
+        }
+        if let Some(v) = args.opt_value_from_str::<_, PathBuf>("--icc-output")? {
+            self.page.icc_output = Some(v);
+        }
+
+        // ConfApp
+        if let Some(v) = args.opt_value_from_str::<_, String>("--password")? {
+            self.app.password = Some(v);
+        }
+        if let Some(v) = args.opt_value_from_str::<_, PathBuf>("--list")? {
+            self.app.list = Some(v);
+        }
+        if args.contains("--watch") {
+            self.app.watch = true;
+        }
+
+        // ConfAseprite
+        if let Some(v) = args.opt_value_from_str::<_, String>("--ase-tag")? {
+            self.aseprite.tag = Some(v);
+        }
+        if let Some(v) = args.opt_value_from_str::<_, String>("--ase-hide-layers")? {
+            self.aseprite.hidden_layers = v.split(',').map(|s| s.to_string()).collect();
+        }
+        if args.contains("--ase-slices") {
+            self.aseprite.show_slices = true;
+        }
+
         // ConfMisc
         if let Some(v) = args.opt_value_from_str::<_, u8>("--padding-filename")? {
             self.misc.padding_filename = v;
@@ -253,10 +407,35 @@ impl Config {
         }
 
         // ConfApp
-        self.app.target = args.free_from_str().unwrap_or_else(|_| {
-            println!("{}", gen_help());
-            exit(0);
-        });
+        match args.free_from_str::<PathBuf>() {
+            Ok(v) => {
+                self.app.target = v.clone();
+                self.app.targets = vec![v];
+
+                // e.g. `rmg a.cbz b.cbz dir/`
+                for v in args.finish() {
+                    let v = PathBuf::from(v);
+                    if v != Path::new("-") && v.to_string_lossy().starts_with('-') {
+                        eyre::bail!("unknown option: {}", v.display());
+                    }
+
+                    self.app.targets.push(v);
+                }
+
+                let stdin = self.app.targets.iter().filter(|v| *v == Path::new("-"));
+                if stdin.count() > 1 {
+                    eyre::bail!("stdin `-` can only be given once");
+                }
+            }
+
+            // `--list` does not need <path>
+            Err(_) if self.app.list.is_some() => {}
+
+            Err(_) => {
+                println!("{}", gen_help());
+                exit(0);
+            }
+        }
 
         Ok(())
     }
@@ -287,12 +466,18 @@ rmg {VERSION}
 Tiny And Fast Manga/Image Viewer
 
 USAGE:
-    rmg [OPTIONS] [FLAGS] <path>
+    rmg [OPTIONS] [FLAGS] <path>...
 
 ARGS:
     <path> A file or directory.
+           Or `-` to read an image or an archive from stdin.
+           Multiple paths are opened as a playlist.
 
 FLAGS:
+        --watch
+            Rescan the directory and append new pages.
+        --ase-slices
+            Draw slice boundaries of Aseprite files.
 
 OPTIONS:
     -h, --help
@@ -301,6 +486,12 @@ OPTIONS:
             Specify the config path.
         --padding-filename
             Padding filename with `0`.
+        --list
+            Read newline-separated paths from a file or `-`(stdin).
+            e.g. `find . -name '*.jpg' | rmg --list -`
+        --password
+            Password for encrypted archives.
+            Or set `RMG_PASSWORD`.
 
 OPTIONS(for Canvas):
         --page-size
@@ -309,6 +500,28 @@ OPTIONS(for Canvas):
         --layout
             Specify layout.
             e.g. `rmg --layout double`
+        --pre-load-nums
+            Pages decoded ahead in the reading direction, in the vertical and
+            double layouts.
+        --cache-mb
+            Memory for decoded pages out of view, in MB.
+        --tone-map
+            Tone mapping for HDR and EXR: aces, reinhard, exposure.
+        --exposure
+            Exposure in stops for HDR and EXR.
+            e.g. `rmg --exposure -1.5`
+        --color-management
+            Convert embedded ICC profiles, default: true.
+            e.g. `rmg --color-management false`
+        --icc-output
+            ICC profile of the display, default: sRGB.
+
+OPTIONS(for Aseprite):
+        --ase-tag
+            Play only this tag.
+        --ase-hide-layers
+            Comma-separated layer names.
+            e.g. `rmg --ase-hide-layers bg,sketch`
 "#,
     )
 }
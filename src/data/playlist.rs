@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+// rmg a.cbz b.cbz dir/
+//   # inner[0], inner[1], inner[2]
+#[derive(Debug, Default, Clone)]
+pub struct Playlist {
+    pub inner: Vec<PathBuf>,
+    pub index: usize,
+}
+
+impl Playlist {
+    pub fn new(inner: Vec<PathBuf>) -> Self {
+        Self { inner, index: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn current(&self) -> Option<&Path> {
+        self.inner.get(self.index).map(|p| p.as_path())
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.index + 1 < self.len()
+    }
+
+    pub fn has_prev(&self) -> bool {
+        self.index > 0
+    }
+
+    pub fn next(&mut self) -> Option<&Path> {
+        if !self.has_next() {
+            return None;
+        }
+
+        self.index += 1;
+        self.current()
+    }
+
+    pub fn prev(&mut self) -> Option<&Path> {
+        if !self.has_prev() {
+            return None;
+        }
+
+        self.index -= 1;
+        self.current()
+    }
+
+    pub fn replace_current(&mut self, path: PathBuf) {
+        match self.inner.get_mut(self.index) {
+            Some(v) => *v = path,
+            None => self.inner.push(path),
+        }
+    }
+
+    // e.g. [2/3] b.cbz
+    pub fn position(&self) -> String {
+        let name = self
+            .current()
+            .and_then(|p| p.file_name())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        format!("[{}/{}] {name}", self.index + 1, self.len())
+    }
+}
@@ -1,15 +1,31 @@
+pub mod anim;
+pub mod exif;
+pub mod hdr;
 pub mod heic;
+pub mod icc;
+pub mod jpeg;
+pub mod kra;
+pub mod psd;
 pub mod svg;
+pub mod tiff;
 
 // feature
 pub mod aseprite;
 pub mod avif;
+pub mod jxl;
 
 use crate::*;
 
+use eyre::OptionExt;
 use fir::ResizeAlg;
 use image;
-use std::{io::Cursor, mem, num::NonZeroU32};
+use std::{
+    io::Cursor,
+    mem,
+    num::NonZeroU32,
+    sync::{mpsc::Receiver, Mutex},
+    time::Instant,
+};
 
 #[derive(Clone)]
 pub struct Frame {
@@ -18,6 +34,9 @@ pub struct Frame {
 
     pub size: Size,
     pub vertex: Rect,
+
+    /// tag and layers of Aseprite files
+    pub ase: Option<frame::aseprite::AseOpts>,
 }
 
 #[derive(Clone)]
@@ -32,13 +51,58 @@ pub enum FrameData {
         index: usize,
         len: usize,
 
+        /// duration of each frame, in ms
         pts: Vec<f32>,
-        delay: f32,
+        /// when the current frame ends, `None` before the first draw
+        deadline: Option<Instant>,
+
+        /// times to play, `0` means forever
+        loops: u32,
+        played: u32,
+        playback: Playback,
     },
 
     DynSingle {
         data: Pixels,
     },
+
+    /// Large animations, decoded by a background thread.
+    ///
+    /// Only a few frames ahead are kept in memory.
+    Stream {
+        rx: Arc<Mutex<Receiver<frame::anim::StreamItem>>>,
+        cur: Pixels,
+
+        index: usize,
+        /// frames seen so far
+        len: usize,
+
+        /// duration of `cur`, in ms
+        pts: f32,
+        deadline: Option<Instant>,
+        /// set by `Frame::flip()`, applied to each received frame
+        flipped: Option<Size>,
+
+        loops: u32,
+        played: u32,
+        playback: Playback,
+    },
+}
+
+/// Shared by all animations on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Playback {
+    pub paused: bool,
+    pub speed: f32,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: 1.0,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -46,18 +110,33 @@ pub enum FrameFmt {
     // single frame
     Jpeg,
     Png,
-    // Apng, // no plans
+    Bmp,
+    Qoi,
+    Ico,
+    Pnm,
+
+    // multi-page, each page is a `Page`.
+    Tiff,
+
+    // tone mapped into 8-bit.
+    Hdr,
+    Exr,
+
+    // flattened preview, layers are ignored.
+    Psd,
+    Kra,
 
     // AVIF and HEIC are both in HEIF container.
-    // no animation support.
+    // AVIF has animation support with `de_avif`.
     Avif,
     Heic,
 
     // has animation support.
+    Apng,
     Aseprite,
     Webp,
     Gif,
-    // Jxl, // ?
+    Jxl,
 
     // no animation support.
     Svg,
@@ -125,6 +204,12 @@ impl Pixels {
     }
 }
 
+impl FrameFmt {
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, Self::Hdr | Self::Exr)
+    }
+}
+
 impl Frame {
     pub fn flip(&mut self) {
         match &mut self.data {
@@ -136,7 +221,16 @@ impl Frame {
                 }
             }
 
-            _ => unimplemented!(),
+            // frames ahead are flipped when received
+            FrameData::Stream { cur, flipped, .. } => {
+                cur.flip(self.size);
+                *flipped = match flipped {
+                    Some(_) => None,
+                    None => Some(self.size),
+                };
+            }
+
+            FrameData::DynSingle { data } => data.flip(self.size),
         }
     }
 
@@ -145,6 +239,13 @@ impl Frame {
     }
 
     pub fn free(&mut self) {
+        // stops the decoding thread
+        if let FrameData::Stream { .. } = self.data {
+            self.data = FrameData::Single {
+                data: Pixels::RGBA { inner: vec![] },
+            };
+        }
+
         match &mut self.data {
             FrameData::Single { data } | FrameData::DynSingle { data } => data.free(),
 
@@ -152,8 +253,89 @@ impl Frame {
                 data.clear();
                 data.shrink_to(0);
             }
+
+            FrameData::Stream { .. } => unreachable!(),
         }
     }
+
+    /// Moves the pixels out, keeps the format and the size.
+    pub fn take(&mut self) -> Self {
+        let data = mem::replace(
+            &mut self.data,
+            FrameData::Single {
+                data: Pixels::RGBA { inner: vec![] },
+            },
+        );
+
+        Self {
+            fmt: self.fmt.clone(),
+            data,
+            size: self.size,
+            vertex: self.vertex,
+            ase: self.ase.clone(),
+        }
+    }
+
+    /// Gray box with `text`, e.g. the filename and the error, shown for pages
+    /// failed to decode.
+    pub fn placeholder(size: Size, text: &str) -> Self {
+        use crate::ui::text::{draw_text, wrap_text, CELL_H, CELL_W};
+
+        const BG: u32 = 0x30_30_30_ff;
+        const FG: u32 = 0xc0_30_30_ff;
+        const TEXT: u32 = 0xe0_e0_e0_ff;
+        const MARGIN: usize = 8;
+
+        let (w, h) = (size.width() as usize, size.height() as usize);
+        let mut inner = vec![BG; w * h];
+
+        // border
+        let mut put = |x: usize, y: usize| {
+            if x < w && y < h {
+                inner[y * w + x] = FG;
+            }
+        };
+        for x in 0..w {
+            put(x, 0);
+            put(x, h.saturating_sub(1));
+        }
+        for y in 0..h {
+            put(0, y);
+            put(w.saturating_sub(1), y);
+        }
+
+        let scale = if w >= 600 { 2 } else { 1 };
+        let cols = w.saturating_sub(MARGIN * 2) / (CELL_W * scale);
+        for (n, line) in wrap_text(text, cols).iter().enumerate() {
+            let y = MARGIN + n * CELL_H * scale;
+            if y + CELL_H * scale > h {
+                break;
+            }
+
+            draw_text(&mut inner, w, MARGIN, y, scale, TEXT, line);
+        }
+
+        Self {
+            fmt: FrameFmt::Unknown,
+            data: FrameData::Single {
+                data: Pixels::RGBA { inner },
+            },
+            size,
+            vertex: Rect::new_at_zero(size),
+            ase: None,
+        }
+    }
+
+    /// Decoded bytes in memory, frames ahead of a stream are not counted.
+    pub fn byte_len(&self) -> usize {
+        let len = match &self.data {
+            FrameData::Single { data } | FrameData::DynSingle { data } => data.as_bytes().len(),
+            FrameData::Multi { data, .. } => data.iter().map(|v| v.as_bytes().len()).sum(),
+            FrameData::Stream { cur, .. } => cur.as_bytes().len(),
+        };
+
+        len * 4
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -167,16 +349,39 @@ impl Frame {
         &self.vertex
     }
 
-    pub fn resize(blob: &[u8], dst_size: Size, algo: ResizeAlg) -> eyre::Result<Self> {
+    /// Detects the format and the original size.
+    pub fn probe(blob: &[u8]) -> eyre::Result<(FrameFmt, Size)> {
         use imagesize::{blob_size, image_type, ImageType};
 
+        if frame::svg::is_svg(blob) {
+            return Ok((FrameFmt::Svg, frame::svg::svg_size(blob)?));
+        }
+
+        if frame::kra::is_kra(blob) {
+            let size = blob_size(&frame::kra::load_kra(blob)?)?;
+
+            return Ok((
+                FrameFmt::Kra,
+                Size::new(size.width as f32, size.height as f32),
+            ));
+        }
+
         let ty = image_type(blob)?;
         let size = blob_size(blob)?;
 
         let fmt = {
             match ty {
                 ImageType::Jpeg => FrameFmt::Jpeg,
+                ImageType::Png if is_apng(blob) => FrameFmt::Apng,
                 ImageType::Png => FrameFmt::Png,
+                ImageType::Bmp => FrameFmt::Bmp,
+                ImageType::Qoi => FrameFmt::Qoi,
+                ImageType::Ico => FrameFmt::Ico,
+                ImageType::Pnm => FrameFmt::Pnm,
+                ImageType::Tiff => FrameFmt::Tiff,
+                ImageType::Psd => FrameFmt::Psd,
+                ImageType::Hdr => FrameFmt::Hdr,
+                ImageType::Exr => FrameFmt::Exr,
 
                 ImageType::Heif(imagesize::Compression::Av1) => FrameFmt::Avif,
                 ImageType::Heif(imagesize::Compression::Hevc) => FrameFmt::Heic,
@@ -186,20 +391,159 @@ impl Frame {
                 ImageType::Aseprite => FrameFmt::Aseprite,
                 ImageType::Gif => FrameFmt::Gif,
                 ImageType::Webp => FrameFmt::Webp,
+                ImageType::Jxl => FrameFmt::Jxl,
 
-                _ => unimplemented!(),
+                _ => eyre::bail!("ERROR: Unknown Format"),
             }
         };
 
         let size = Size::new(size.width as f32, size.height as f32);
 
+        // displayed size
+        let size = {
+            if frame::exif::is_transposed(exif_orientation(&fmt, blob)) {
+                Size::new(size.height(), size.width())
+            } else {
+                size
+            }
+        };
+
+        Ok((fmt, size))
+    }
+
+    /// Same as `probe()`, but for the `nth` page of multi-page formats.
+    pub fn probe_nth(blob: &[u8], nth: usize) -> eyre::Result<(FrameFmt, Size)> {
+        match Self::probe(blob)? {
+            (FrameFmt::Tiff, ..) if nth > 0 => {
+                Ok((FrameFmt::Tiff, frame::tiff::page_size(blob, nth)?))
+            }
+
+            res => Ok(res),
+        }
+    }
+
+    pub fn resize(blob: &[u8], dst_size: Size, algo: ResizeAlg) -> eyre::Result<Self> {
+        Self::resize_nth(blob, 0, dst_size, algo, algo, None)
+    }
+
+    /// `anim_algo` is used for frames of animations.
+    ///
+    /// `ase` is from the previous frame of the same page, or from the config if `None`.
+    pub fn resize_nth(
+        blob: &[u8],
+        nth: usize,
+        dst_size: Size,
+        algo: ResizeAlg,
+        anim_algo: ResizeAlg,
+        ase: Option<frame::aseprite::AseOpts>,
+    ) -> eyre::Result<Self> {
+        let mut ase = ase;
+
+        let (fmt, size) = Self::probe_nth(blob, nth)?;
+
+        // `size` is rotated, `raw_size` is the decoded one.
+        let orientation = exif_orientation(&fmt, blob);
+        let raw_size = {
+            if frame::exif::is_transposed(orientation) {
+                Size::new(size.height(), size.width())
+            } else {
+                size
+            }
+        };
+
         let data = {
             match fmt {
-                FrameFmt::Jpeg | FrameFmt::Png | FrameFmt::Avif => {
-                    let img = image::load_from_memory(blob)?;
+                FrameFmt::Avif if cfg!(feature = "de_avif") => {
+                    let (.., pts, mut frames) = frame::avif::load_avif(blob)?;
+                    for frame in frames.iter_mut() {
+                        frame::exif::apply_orientation(frame, raw_size, orientation);
+                    }
+
+                    // the repetition count is not read, sequences loop forever
+                    frames_to_data(frames, pts, 0, size, dst_size, algo, anim_algo)?
+                }
+
+                FrameFmt::Tiff => {
+                    let (.., mut data) = frame::tiff::load_tiff(blob, nth)?;
+                    frame::icc::transform(blob, &mut data);
+                    resize_rgba8(&mut data, size, dst_size, algo)?;
+
+                    FrameData::Single {
+                        data: Pixels::from_rgba_bytes(data),
+                    }
+                }
+
+                FrameFmt::Hdr | FrameFmt::Exr => {
+                    let (.., mut data) = frame::hdr::load_hdr(blob)?;
+                    resize_rgba8(&mut data, size, dst_size, algo)?;
+
+                    FrameData::Single {
+                        data: Pixels::from_rgba_bytes(data),
+                    }
+                }
+
+                FrameFmt::Psd => {
+                    let (.., mut data) = frame::psd::load_psd(blob)?;
+                    resize_rgba8(&mut data, size, dst_size, algo)?;
+
+                    FrameData::Single {
+                        data: Pixels::from_rgba_bytes(data),
+                    }
+                }
+
+                FrameFmt::Kra => {
+                    let img = image::load_from_memory(&frame::kra::load_kra(blob)?)?;
                     let mut data = img.to_rgba8().to_vec();
                     resize_rgba8(&mut data, size, dst_size, algo)?;
 
+                    FrameData::Single {
+                        data: Pixels::from_rgba_bytes(data),
+                    }
+                }
+
+                FrameFmt::Jpeg
+                | FrameFmt::Png
+                | FrameFmt::Bmp
+                | FrameFmt::Qoi
+                | FrameFmt::Ico
+                | FrameFmt::Pnm
+                | FrameFmt::Avif
+                | FrameFmt::Webp
+                    // still WebP
+                    if !matches!(fmt, FrameFmt::Webp) || !is_anim_webp(blob) =>
+                {
+                    let transposed = frame::exif::is_transposed(orientation);
+
+                    // reduced-resolution decoding of large JPEGs, then finish with `fir`
+                    let scaled = match fmt {
+                        FrameFmt::Jpeg if transposed => frame::jpeg::load_scaled(
+                            blob,
+                            Size::new(dst_size.height(), dst_size.width()),
+                        ),
+                        FrameFmt::Jpeg => frame::jpeg::load_scaled(blob, dst_size),
+
+                        _ => None,
+                    };
+                    let (mut data, raw_size) = match scaled {
+                        Some((raw_size, data)) => (data, raw_size),
+                        None => {
+                            let img = image::load_from_memory(blob)?;
+
+                            (img.to_rgba8().to_vec(), raw_size)
+                        }
+                    };
+                    let size = {
+                        if transposed {
+                            Size::new(raw_size.height(), raw_size.width())
+                        } else {
+                            raw_size
+                        }
+                    };
+
+                    frame::icc::transform(blob, &mut data);
+                    frame::exif::apply_orientation(&mut data, raw_size, orientation);
+                    resize_rgba8(&mut data, size, dst_size, algo)?;
+
                     FrameData::Single {
                         data: Pixels::from_rgba_bytes(data.to_vec()),
                     }
@@ -213,138 +557,245 @@ impl Frame {
                     }
                 }
 
-                // TODO: no resize
-                FrameFmt::Gif | FrameFmt::Webp => {
-                    use image::{
-                        codecs::gif::GifDecoder, codecs::webp::WebPDecoder, AnimationDecoder,
+                FrameFmt::Gif | FrameFmt::Webp | FrameFmt::Apng => {
+                    let opts = frame::anim::AnimOpts {
+                        fmt: fmt.clone(),
+                        orientation,
+                        algo: anim_algo,
+                        raw_size,
+                        size,
+                        dst_size,
                     };
+                    let xfm = frame::icc::new_transform(blob);
 
+                    let mut data = Vec::with_capacity(30);
                     let mut pts = Vec::with_capacity(30);
-                    let mut len = 0;
-
-                    FrameData::Multi {
-                        data: {
-                            let cur = Cursor::new(blob);
-
-                            let frames = {
-                                match fmt {
-                                    FrameFmt::Gif => {
-                                        GifDecoder::new(cur)?.into_frames().collect_frames()?
-                                    }
+                    let mut bytes = 0;
+                    let mut is_large = false;
 
-                                    FrameFmt::Webp => {
-                                        WebPDecoder::new(cur)?.into_frames().collect_frames()?
-                                    }
+                    for frame in frame::anim::frames(&fmt, Cursor::new(blob))? {
+                        let (pixels, delay) =
+                            frame::anim::prepare_frame(frame?, &opts, xfm.as_ref())?;
 
-                                    _ => unreachable!(),
-                                }
-                            };
-
-                            len = frames.len();
-                            let mut data = Vec::with_capacity(frames.len());
-                            for frame in frames {
-                                let delay = frame.delay().numer_denom_ms().0 as f32;
-                                pts.push(delay);
-
-                                let mut frame = frame.buffer().to_vec();
-
-                                if size.width() < dst_size.width() {
-                                    let (bgw, bgh) =
-                                        (dst_size.width() as usize, size.height() as usize);
-                                    let bg = &mut vec![0; bgw * 4 * bgh];
-                                    let fg = &mut frame;
-                                    let (fgw, fgh) = (size.width() as usize, bgh);
-
-                                    center_img(bg, fg, bgw, fgw, fgh);
-                                    bg.shrink_to_fit();
-
-                                    fg.clear();
-                                    fg.shrink_to(0);
-
-                                    mem::swap(bg, fg);
-                                } else if size.width() > dst_size.width() {
-                                    resize_rgba8(&mut frame, size, dst_size, algo)?;
-                                } else if size.width() == dst_size.width() {
-                                    // doing nothing
-                                }
-
-                                data.push(Pixels::from_rgba_bytes(frame))
-                            }
+                        bytes += pixels.as_bytes().len() * 4;
+                        data.push(pixels);
+                        pts.push(delay);
 
-                            data
-                        },
+                        if bytes > frame::anim::mem_limit() {
+                            is_large = true;
+                            break;
+                        }
+                    }
 
-                        index: 0,
-                        delay: 0.0,
-                        len,
-                        pts,
+                    if is_large {
+                        // the first frames start the ring, others are decoded in the background
+                        let head = data.into_iter().zip(pts).collect();
+                        let rx = frame::anim::spawn_stream(Arc::from(blob), opts, head);
+                        let (index, cur, pts) = rx.recv()?;
+
+                        FrameData::Stream {
+                            rx: Arc::new(Mutex::new(rx)),
+                            cur,
+                            index,
+                            len: index + 1,
+                            pts,
+                            deadline: None,
+                            flipped: None,
+                            loops: loop_count(&fmt, blob),
+                            played: 0,
+                            playback: Playback::default(),
+                        }
+                    } else {
+                        FrameData::Multi {
+                            index: 0,
+                            deadline: None,
+                            loops: loop_count(&fmt, blob),
+                            played: 0,
+                            playback: Playback::default(),
+                            len: data.len(),
+                            data,
+                            pts,
+                        }
                     }
                 }
                 FrameFmt::Aseprite => {
-                    let (.., pts, frames) = frame::aseprite::load_aseprite(&blob)?;
+                    let opts = ase.get_or_insert_with(frame::aseprite::AseOpts::init);
+                    let (.., pts, frames) = frame::aseprite::load_aseprite(blob, opts)?;
 
                     let mut data = Vec::with_capacity(pts.len());
                     for frame in frames.into_iter() {
                         let mut frame = frame;
-
-                        if size.width() < dst_size.width() {
-                            let (bgw, bgh) = (dst_size.width() as usize, size.height() as usize);
-                            let bg = &mut vec![0; bgw * 4 * bgh];
-                            let fg = &mut frame;
-                            let (fgw, fgh) = (size.width() as usize, bgh);
-
-                            center_img(bg, fg, bgw, fgw, fgh);
-                            bg.shrink_to_fit();
-
-                            fg.clear();
-                            fg.shrink_to(0);
-
-                            mem::swap(bg, fg);
-                        } else if size.width() > dst_size.width() {
-                            resize_rgba8(&mut frame, size, dst_size, algo)?;
-                        } else if size.width() == dst_size.width() {
-                            // doing nothing
-                        }
+                        fit_anim_frame(&mut frame, size, dst_size, anim_algo)?;
 
                         data.push(Pixels::from_rgba_bytes(frame))
                     }
 
+                    // Aseprite files have no loop count, tags loop forever
                     FrameData::Multi {
                         index: 0,
-                        delay: 0.0,
+                        deadline: None,
+                        loops: 0,
+                        played: 0,
+                        playback: Playback::default(),
                         len: pts.len(),
                         data,
                         pts,
                     }
                 }
 
-                FrameFmt::Svg => todo!(),
+                FrameFmt::Jxl => {
+                    let (_, loops, pts, frames) = frame::jxl::load_jxl(blob)?;
 
-                _ => unimplemented!(),
+                    frames_to_data(frames, pts, loops, size, dst_size, algo, anim_algo)?
+                }
+
+                // vector, no resize
+                FrameFmt::Svg => FrameData::Single {
+                    data: Pixels::from_rgba_bytes(frame::svg::load_svg(blob, dst_size)?),
+                },
+
+                _ => eyre::bail!("ERROR: Unsupported Format"),
             }
         };
 
+        // broken animations, `next_frame()` and `step()` need a frame
+        if let FrameData::Multi { data, pts, .. } = &data {
+            if data.is_empty() || data.len() != pts.len() {
+                eyre::bail!("ERROR: No frames decoded")
+            }
+        }
+
         let vertex = Rect::new_at_zero(dst_size);
 
+        // only kept for Aseprite
+        if !matches!(fmt, FrameFmt::Aseprite) {
+            ase = None;
+        }
+
         Ok(Self {
             fmt,
             data,
             vertex,
             size: dst_size,
+            ase,
         })
     }
 
     pub fn ty(&self) -> FrameTy {
         match self.data {
             FrameData::Single { .. } => FrameTy::Img,
-            FrameData::Multi { .. } => FrameTy::Anim,
+            FrameData::Multi { .. } | FrameData::Stream { .. } => FrameTy::Anim,
             FrameData::DynSingle { .. } => FrameTy::Img,
         }
     }
 }
 
 impl FrameData {
+    /// When the next frame should be drawn.
+    pub fn deadline(&self) -> Option<Instant> {
+        match self {
+            Self::Multi {
+                deadline, playback, ..
+            }
+            | Self::Stream {
+                deadline, playback, ..
+            } if !playback.paused && !self.is_finished() => *deadline,
+
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the loop count is reached.
+    pub fn is_finished(&self) -> bool {
+        match self {
+            Self::Multi { loops, played, .. } | Self::Stream { loops, played, .. } => {
+                *loops > 0 && played >= loops
+            }
+            _ => false,
+        }
+    }
+
+    /// `(index, len)` of the current frame.
+    pub fn frame_index(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Multi { index, len, .. } | Self::Stream { index, len, .. } => {
+                Some((*index, *len))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_playback(&mut self, value: Playback) {
+        if let Self::Multi {
+            playback, deadline, ..
+        }
+        | Self::Stream {
+            playback, deadline, ..
+        } = self
+        {
+            if *playback != value {
+                // restart the current frame
+                *deadline = None;
+                *playback = value;
+            }
+        }
+    }
+
+    /// Moves `step` frames and restarts the current frame.
+    ///
+    /// Streamed animations can only move forward.
+    pub fn step(&mut self, step: isize) {
+        match self {
+            Self::Multi {
+                index,
+                len,
+                deadline,
+                played,
+                ..
+            } => {
+                *index = (*index as isize + step).rem_euclid(*len as isize) as usize;
+                *deadline = None;
+                *played = 0;
+            }
+
+            Self::Stream {
+                rx,
+                cur,
+                index,
+                len,
+                pts,
+                deadline,
+                flipped,
+                played,
+                ..
+            } => {
+                let Ok(rx) = rx.lock() else {
+                    return;
+                };
+
+                for _ in 0..step.max(0) {
+                    let Ok((i, mut pixels, delay)) = rx.recv() else {
+                        break;
+                    };
+                    if let Some(size) = flipped {
+                        pixels.flip(*size);
+                    }
+
+                    (*index, *cur, *pts) = (i, pixels, delay);
+                    *len = (*len).max(i + 1);
+                }
+
+                *deadline = None;
+                *played = 0;
+            }
+
+            _ => {}
+        }
+    }
+
     pub fn next_frame(&mut self) -> &Pixels {
+        let is_finished = self.is_finished();
+
         match self {
             Self::Single { ref data, .. } => &data,
 
@@ -353,30 +804,120 @@ impl FrameData {
                 ref mut index,
                 ref len,
                 ref pts,
-                ref mut delay,
+                ref mut deadline,
+                ref loops,
+                ref mut played,
+                ref playback,
             } => {
-                let pts = pts[*index];
-                let t = *delay - pts;
+                if playback.paused || is_finished {
+                    return &data[*index];
+                }
 
-                // timeout
-                if t > 0.0 {
-                    *delay = t;
+                let now = Instant::now();
+                let speed = playback.speed.max(0.01);
+                let dur =
+                    |index: usize| Duration::from_secs_f32(pts[index].max(0.0) / 1000.0 / speed);
 
-                    if *index + 1 < *len {
-                        *index += 1;
-                    } else {
-                        // reset
-                        *index = 0;
-                    }
+                match deadline {
+                    // first frame
+                    None => *deadline = Some(now + dur(*index)),
+
+                    // all frames are 0ms
+                    Some(..) if pts.iter().sum::<f32>() <= 0.0 => {}
+
+                    Some(end) => {
+                        // e.g. the page was scrolled out of view
+                        let total: f32 = pts.iter().sum::<f32>() / speed;
+                        if now.duration_since(*end).as_secs_f32() * 1000.0 > total {
+                            *end = now;
+                        }
+
+                        // skip frames if the redraw is late
+                        while now >= *end {
+                            if *index + 1 < *len {
+                                *index += 1;
+                            } else {
+                                *played += 1;
 
-                // unready
-                } else {
-                    *delay += 1000.0 / 24.0;
+                                // stay on the last frame
+                                if *loops > 0 && *played >= *loops {
+                                    break;
+                                }
+
+                                *index = 0;
+                            }
+
+                            *end += dur(*index);
+                        }
+                    }
                 }
 
                 &data[*index]
             }
 
+            Self::Stream {
+                ref rx,
+                ref mut cur,
+                ref mut index,
+                ref mut len,
+                ref mut pts,
+                ref mut deadline,
+                ref flipped,
+                ref loops,
+                ref mut played,
+                ref playback,
+            } => {
+                if playback.paused || is_finished {
+                    return cur;
+                }
+
+                let now = Instant::now();
+                let speed = playback.speed.max(0.01);
+                let dur = |pts: f32| Duration::from_secs_f32(pts.max(0.0) / 1000.0 / speed);
+
+                let Some(end) = deadline else {
+                    // first frame
+                    *deadline = Some(now + dur(*pts));
+
+                    return cur;
+                };
+
+                // e.g. the page was scrolled out of view
+                if now.duration_since(*end) > Duration::from_secs(1) {
+                    *end = now;
+                }
+
+                let Ok(rx) = rx.lock() else {
+                    return cur;
+                };
+
+                while now >= *end {
+                    // the decoder is behind, keep the current frame
+                    let Ok((i, mut pixels, delay)) = rx.try_recv() else {
+                        break;
+                    };
+                    if let Some(size) = flipped {
+                        pixels.flip(*size);
+                    }
+
+                    // next loop
+                    if i < *index {
+                        *played += 1;
+
+                        // stay on the last frame
+                        if *loops > 0 && *played >= *loops {
+                            break;
+                        }
+                    }
+
+                    (*index, *cur, *pts) = (i, pixels, delay);
+                    *len = (*len).max(i + 1);
+                    *end += dur(delay);
+                }
+
+                cur
+            }
+
             Self::DynSingle { ref data, .. } => &data,
         }
     }
@@ -387,6 +928,16 @@ impl From<&str> for FrameFmt {
         match value {
             "jpg" | "jpeg" => Self::Jpeg,
             "png" => Self::Png,
+            "apng" => Self::Apng,
+            "bmp" => Self::Bmp,
+            "qoi" => Self::Qoi,
+            "ico" => Self::Ico,
+            "pbm" | "pgm" | "ppm" | "pnm" | "pam" => Self::Pnm,
+            "tif" | "tiff" => Self::Tiff,
+            "hdr" => Self::Hdr,
+            "exr" => Self::Exr,
+            "psd" | "psb" => Self::Psd,
+            "kra" | "ora" => Self::Kra,
 
             "heic" | "heif" => Self::Heic,
             "avif" => Self::Avif,
@@ -394,6 +945,7 @@ impl From<&str> for FrameFmt {
             "ase" | "aseprite" => Self::Aseprite,
             "webp" => Self::Webp,
             "gif" => Self::Gif,
+            "jxl" => Self::Jxl,
 
             "svg" => Self::Svg,
 
@@ -411,6 +963,7 @@ impl Default for Frame {
             },
             size: Size::default(),
             vertex: Rect::default(),
+            ase: None,
         }
     }
 }
@@ -420,7 +973,8 @@ impl Frame {
         match &self.data {
             FrameData::Single { data } => data.as_argb_bytes(),
             FrameData::Multi { data, index, .. } => data[*index].as_argb_bytes(),
-            _ => todo!(),
+            FrameData::DynSingle { data } => data.as_argb_bytes(),
+            FrameData::Stream { cur, .. } => cur.as_argb_bytes(),
         }
     }
 
@@ -463,6 +1017,127 @@ impl Frame {
     // }
 }
 
+// HEIC is rotated by libheif with `irot` and `imir`.
+fn exif_orientation(fmt: &FrameFmt, blob: &[u8]) -> u32 {
+    match fmt {
+        FrameFmt::Jpeg | FrameFmt::Avif | FrameFmt::Webp => frame::exif::orientation(blob),
+
+        _ => 1,
+    }
+}
+
+// Returns the times to play, `0` means forever.
+fn loop_count(fmt: &FrameFmt, blob: &[u8]) -> u32 {
+    let find = |tag: &[u8]| {
+        blob.windows(tag.len())
+            .position(|w| w == tag)
+            .map(|i| i + tag.len())
+    };
+
+    match fmt {
+        // NETSCAPE2.0 extension, counts the repeats. Plays once without it.
+        FrameFmt::Gif => match find(b"NETSCAPE2.0\x03\x01") {
+            Some(i) if i + 2 <= blob.len() => match u16::from_le_bytes([blob[i], blob[i + 1]]) {
+                0 => 0,
+                n => n as u32 + 1,
+            },
+
+            _ => 1,
+        },
+
+        // acTL: num_frames, num_plays
+        FrameFmt::Apng => match find(b"acTL") {
+            Some(i) if i + 8 <= blob.len() => {
+                u32::from_be_bytes([blob[i + 4], blob[i + 5], blob[i + 6], blob[i + 7]])
+            }
+
+            _ => 0,
+        },
+
+        // ANIM: background color, loop count
+        FrameFmt::Webp => match find(b"ANIM") {
+            Some(i) if i + 10 <= blob.len() => {
+                u16::from_le_bytes([blob[i + 8], blob[i + 9]]) as u32
+            }
+
+            _ => 0,
+        },
+
+        _ => 0,
+    }
+}
+
+// WebP with `ANIM` chunk.
+fn is_anim_webp(blob: &[u8]) -> bool {
+    use image::codecs::webp::WebPDecoder;
+
+    WebPDecoder::new(Cursor::new(blob))
+        .map(|decoder| decoder.has_animation())
+        .unwrap_or(false)
+}
+
+// PNG with `acTL` chunk.
+fn is_apng(blob: &[u8]) -> bool {
+    use image::codecs::png::PngDecoder;
+
+    PngDecoder::new(Cursor::new(blob))
+        .and_then(|decoder| decoder.is_apng())
+        .unwrap_or(false)
+}
+
+// Single frame as an image, or more as an animation played `loops` times.
+fn frames_to_data(
+    frames: Vec<Vec<u8>>,
+    pts: Vec<f32>,
+    loops: u32,
+    size: Size,
+    dst_size: Size,
+    algo: ResizeAlg,
+    anim_algo: ResizeAlg,
+) -> eyre::Result<FrameData> {
+    if frames.len() == 1 {
+        let mut data = frames.into_iter().next().unwrap();
+        resize_rgba8(&mut data, size, dst_size, algo)?;
+
+        return Ok(FrameData::Single {
+            data: Pixels::from_rgba_bytes(data),
+        });
+    }
+
+    let mut data = Vec::with_capacity(pts.len());
+    for frame in frames.into_iter() {
+        let mut frame = frame;
+        fit_anim_frame(&mut frame, size, dst_size, anim_algo)?;
+
+        data.push(Pixels::from_rgba_bytes(frame))
+    }
+
+    Ok(FrameData::Multi {
+        index: 0,
+        deadline: None,
+        loops,
+        played: 0,
+        playback: Playback::default(),
+        len: pts.len(),
+        data,
+        pts,
+    })
+}
+
+// Scale the animation frame to `dst_size`, same as images.
+pub fn fit_anim_frame(
+    frame: &mut Vec<u8>,
+    size: Size,
+    dst_size: Size,
+    algo: ResizeAlg,
+) -> eyre::Result<()> {
+    if size != dst_size {
+        resize_rgba8(frame, size, dst_size, algo)?;
+    }
+
+    Ok(())
+}
+
 // make sure `bg.height == fg.height`.
 pub fn center_img(bg: &mut [u8], fg: &mut [u8], bgw: usize, fgw: usize, fgh: usize) {
     let x_offset = (bgw * 4 - fgw * 4) / 2;
@@ -488,13 +1163,13 @@ pub fn resize_rgba8(
     let (dw, dh) = (dst.width() as u32, dst.height() as u32);
 
     let mut src_image = fir::Image::from_slice_u8(
-        NonZeroU32::new(sw).unwrap(),
-        NonZeroU32::new(sh).unwrap(),
+        NonZeroU32::new(sw).ok_or_eyre("ERROR: Empty image")?,
+        NonZeroU32::new(sh).ok_or_eyre("ERROR: Empty image")?,
         bytes,
         fir::PixelType::U8x4,
     )?;
-    let dw = NonZeroU32::new(dw).unwrap();
-    let dh = NonZeroU32::new(dh).unwrap();
+    let dw = NonZeroU32::new(dw).ok_or_eyre("ERROR: Empty image")?;
+    let dh = NonZeroU32::new(dh).ok_or_eyre("ERROR: Empty image")?;
 
     let mut dst_image = fir::Image::new(dw, dh, src_image.pixel_type());
     let mut dst_view = dst_image.view_mut();
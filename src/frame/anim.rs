@@ -0,0 +1,146 @@
+use crate::*;
+
+use fir::ResizeAlg;
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, Frames,
+};
+use qcms::Transform;
+use std::{
+    io::{BufRead, Cursor, Seek},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+    },
+};
+
+// Animations larger than this are streamed, in bytes.
+static MEM_LIMIT: AtomicUsize = AtomicUsize::new(256 * 1024 * 1024);
+
+// Max frames decoded ahead of the current frame.
+const RING_LEN: usize = 8;
+
+/// `(index, frame, delay in ms)`
+pub type StreamItem = (usize, Pixels, f32);
+
+pub fn set_mem_limit(mb: usize) {
+    MEM_LIMIT.store(mb * 1024 * 1024, Ordering::Relaxed);
+}
+
+pub fn mem_limit() -> usize {
+    MEM_LIMIT.load(Ordering::Relaxed)
+}
+
+// Decoding parameters of each frame.
+#[derive(Clone)]
+pub struct AnimOpts {
+    pub fmt: FrameFmt,
+    pub orientation: u32,
+    pub algo: ResizeAlg,
+
+    /// decoded size
+    pub raw_size: Size,
+    /// rotated size
+    pub size: Size,
+    pub dst_size: Size,
+}
+
+// blend and dispose ops are applied by `image`
+pub fn frames<'a, R>(fmt: &FrameFmt, r: R) -> eyre::Result<Frames<'a>>
+where
+    R: BufRead + Seek + 'a,
+{
+    Ok(match fmt {
+        FrameFmt::Gif => GifDecoder::new(r)?.into_frames(),
+        FrameFmt::Webp => WebPDecoder::new(r)?.into_frames(),
+        FrameFmt::Apng => PngDecoder::new(r)?.apng()?.into_frames(),
+
+        _ => unreachable!(),
+    })
+}
+
+pub fn prepare_frame(
+    frame: image::Frame,
+    opts: &AnimOpts,
+    xfm: Option<&Transform>,
+) -> eyre::Result<(Pixels, f32)> {
+    let (numer, denom) = frame.delay().numer_denom_ms();
+    let mut delay = numer as f32 / denom.max(1) as f32;
+
+    // same as browsers, 0ms and 10ms are too fast
+    if matches!(opts.fmt, FrameFmt::Gif) && delay <= 10.0 {
+        delay = 100.0;
+    }
+
+    let mut frame = frame.into_buffer().into_raw();
+    if let Some(xfm) = xfm {
+        xfm.apply(&mut frame);
+    }
+    frame::exif::apply_orientation(&mut frame, opts.raw_size, opts.orientation);
+    fit_anim_frame(&mut frame, opts.size, opts.dst_size, opts.algo)?;
+
+    Ok((Pixels::from_rgba_bytes(frame), delay))
+}
+
+/// Decodes frames in a background thread, and loops forever.
+///
+/// `head` are the first frames decoded already, they are sent first and at most
+/// a ring of them is kept. The thread blocks when the ring is full, and exits when
+/// the receiver is dropped.
+pub fn spawn_stream(
+    blob: Arc<[u8]>,
+    opts: AnimOpts,
+    mut head: Vec<(Pixels, f32)>,
+) -> Receiver<StreamItem> {
+    let frame_bytes = (opts.dst_size.len() * 4).max(1);
+    let ring_len = (mem_limit() / frame_bytes).clamp(1, RING_LEN);
+
+    let (tx, rx) = mpsc::sync_channel(ring_len);
+    head.truncate(ring_len);
+
+    thread::spawn(move || {
+        let xfm = frame::icc::new_transform(&blob);
+
+        let mut skip = head.len();
+        for (index, (pixels, delay)) in head.into_iter().enumerate() {
+            if tx.send((index, pixels, delay)).is_err() {
+                return;
+            }
+        }
+
+        loop {
+            let Ok(frames) = frames(&opts.fmt, Cursor::new(blob.clone())) else {
+                return;
+            };
+
+            let mut len = 0;
+            for (index, frame) in frames.enumerate() {
+                // still decoded, the frames are blended in order
+                if index < skip {
+                    len += 1;
+
+                    continue;
+                }
+
+                let res = frame
+                    .map_err(eyre::Report::from)
+                    .and_then(|frame| prepare_frame(frame, &opts, xfm.as_ref()));
+                let Ok((pixels, delay)) = res else {
+                    return;
+                };
+
+                if tx.send((index, pixels, delay)).is_err() {
+                    return;
+                }
+                len += 1;
+            }
+            skip = 0;
+
+            if len == 0 {
+                return;
+            }
+        }
+    });
+
+    rx
+}
@@ -1,36 +1,238 @@
 use crate::Size;
 
-pub fn load_aseprite(_bytes: &[u8]) -> eyre::Result<(Size, Vec<f32>, Vec<Vec<u8>>)> {
+use std::sync::RwLock;
+
+// From the config, each file starts with a copy of it.
+static OPTS: RwLock<AseOpts> = RwLock::new(AseOpts::new());
+
+/// Kept on the `Frame`, changed by keys.
+#[derive(Debug, Clone, Default)]
+pub struct AseOpts {
+    /// play only this tag, or all frames if `None`
+    pub tag: Option<String>,
+    pub hidden_layers: Vec<String>,
+    /// draw slice boundaries
+    pub show_slices: bool,
+
+    // of the loaded file
+    tags: Vec<String>,
+    layers: Vec<String>,
+}
+
+impl AseOpts {
+    const fn new() -> Self {
+        Self {
+            tag: None,
+            hidden_layers: Vec::new(),
+            show_slices: false,
+            tags: Vec::new(),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Options from the config.
+    pub fn init() -> Self {
+        OPTS.read().unwrap().clone()
+    }
+
+    /// Plays the next tag, then all frames.
+    pub fn next_tag(&mut self) -> Option<String> {
+        let next = match self.tag.as_ref() {
+            None => 0,
+            Some(tag) => match self.tags.iter().position(|v| v == tag) {
+                Some(i) => i + 1,
+                None => 0,
+            },
+        };
+        self.tag = self.tags.get(next).cloned();
+
+        self.tag.clone()
+    }
+
+    /// Toggles the `nth` layer from the top, returns `(name, visible)`.
+    pub fn toggle_layer(&mut self, nth: usize) -> Option<(String, bool)> {
+        let name = self.layers.iter().rev().nth(nth)?.clone();
+        let visible = match self.hidden_layers.iter().position(|v| *v == name) {
+            Some(i) => {
+                self.hidden_layers.remove(i);
+
+                true
+            }
+
+            None => {
+                self.hidden_layers.push(name.clone());
+
+                false
+            }
+        };
+
+        Some((name, visible))
+    }
+
+    pub fn toggle_slices(&mut self) -> bool {
+        self.show_slices = !self.show_slices;
+
+        self.show_slices
+    }
+}
+
+pub fn set_opts(tag: Option<String>, hidden_layers: Vec<String>, show_slices: bool) {
+    let mut opts = OPTS.write().unwrap();
+
+    opts.tag = tag;
+    opts.hidden_layers = hidden_layers;
+    opts.show_slices = show_slices;
+}
+
+/// Tags and layers of the file are stored into `opts`.
+pub fn load_aseprite(
+    _bytes: &[u8],
+    _opts: &mut AseOpts,
+) -> eyre::Result<(Size, Vec<f32>, Vec<Vec<u8>>)> {
     #[cfg(feature = "de_aseprite")]
     {
-        return feat::load_aseprite(_bytes);
+        return feat::load_aseprite(_bytes, _opts);
     }
 
-    eyre::bail!("")
+    eyre::bail!("Aseprite support is disabled, enable the `de_aseprite` feature")
 }
 
 #[cfg(feature = "de_aseprite")]
 mod feat {
+    use super::AseOpts;
     use crate::Size;
-    use asefile::AsepriteFile;
-    use std::mem;
+    use asefile::{AnimationDirection, AsepriteFile};
+
+    // RGBA
+    const SLICE_COLOR: [u8; 4] = [255, 0, 255, 255];
 
     #[inline]
-    pub fn load_aseprite(bytes: &[u8]) -> eyre::Result<(Size, Vec<f32>, Vec<Vec<u8>>)> {
-        let ase = AsepriteFile::read(bytes).unwrap();
+    pub fn load_aseprite(
+        bytes: &[u8],
+        opts: &mut AseOpts,
+    ) -> eyre::Result<(Size, Vec<f32>, Vec<Vec<u8>>)> {
+        let ase = AsepriteFile::read(bytes)?;
         let size = Size::new(ase.width() as f32, ase.height() as f32);
-        let head = 0;
-        let tail = ase.num_frames();
 
-        let mut data = Vec::with_capacity(tail as usize);
-        let mut pts = Vec::with_capacity(tail as usize);
-        for index in head..tail {
+        opts.tags = (0..ase.num_tags())
+            .map(|i| ase.tag(i).name().to_string())
+            .collect();
+        // bottom to top
+        opts.layers = (0..ase.num_layers())
+            .map(|id| ase.layer(id).name().to_string())
+            .collect();
+
+        let order = play_order(&ase, opts.tag.as_deref());
+
+        // bottom to top
+        let visible: Vec<u32> = (0..ase.num_layers())
+            .filter(|&id| {
+                let layer = ase.layer(id);
+
+                layer.is_visible() && !opts.hidden_layers.iter().any(|v| v == layer.name())
+            })
+            .collect();
+
+        let mut data = Vec::with_capacity(order.len());
+        let mut pts = Vec::with_capacity(order.len());
+        for index in order {
             let frame = ase.frame(index);
 
+            let mut img = {
+                if opts.hidden_layers.is_empty() {
+                    frame.image().to_vec()
+                } else {
+                    composite(&ase, index, &visible)
+                }
+            };
+
+            if opts.show_slices {
+                draw_slices(&ase, index, &mut img);
+            }
+
             pts.push(frame.duration() as f32);
-            data.push(frame.image().to_vec());
+            data.push(img);
         }
 
         Ok((size, pts, data))
     }
+
+    // Frame indexes of the tag, e.g. ping-pong `0 1 2 3 2 1`.
+    fn play_order(ase: &AsepriteFile, tag: Option<&str>) -> Vec<u32> {
+        let Some(tag) = tag.and_then(|name| ase.tag_by_name(name)) else {
+            return (0..ase.num_frames()).collect();
+        };
+
+        let (from, to) = (tag.from_frame(), tag.to_frame());
+
+        #[allow(unreachable_patterns)]
+        match tag.animation_direction() {
+            AnimationDirection::Reverse => (from..=to).rev().collect(),
+            AnimationDirection::PingPong => (from..=to).chain((from + 1..to).rev()).collect(),
+
+            _ => (from..=to).collect(),
+        }
+    }
+
+    // Blends the cels of `layers` with the normal mode.
+    fn composite(ase: &AsepriteFile, index: u32, layers: &[u32]) -> Vec<u8> {
+        let frame = ase.frame(index);
+        let mut res = vec![0_u8; ase.width() as usize * ase.height() as usize * 4];
+
+        for &id in layers {
+            let cel = frame.layer(id);
+            if cel.is_empty() {
+                continue;
+            }
+
+            let opacity = ase.layer(id).opacity() as f32 / 255.0;
+            let img = cel.image();
+
+            for (dst, src) in res.chunks_exact_mut(4).zip(img.as_raw().chunks_exact(4)) {
+                let sa = src[3] as f32 / 255.0 * opacity;
+                let da = dst[3] as f32 / 255.0;
+                let oa = sa + da * (1.0 - sa);
+                if oa <= 0.0 {
+                    continue;
+                }
+
+                for c in 0..3 {
+                    let v = (src[c] as f32 * sa + dst[c] as f32 * da * (1.0 - sa)) / oa;
+                    dst[c] = v.round() as u8;
+                }
+                dst[3] = (oa * 255.0).round() as u8;
+            }
+        }
+
+        res
+    }
+
+    // 1px outline of each slice, using the last key before `index`.
+    fn draw_slices(ase: &AsepriteFile, index: u32, img: &mut [u8]) {
+        let (w, h) = (ase.width() as i64, ase.height() as i64);
+        let mut put = |x: i64, y: i64| {
+            if (0..w).contains(&x) && (0..h).contains(&y) {
+                let i = ((y * w + x) * 4) as usize;
+                img[i..i + 4].copy_from_slice(&SLICE_COLOR);
+            }
+        };
+
+        for slice in ase.slices() {
+            let Some(key) = slice.keys.iter().filter(|k| k.from_frame <= index).last() else {
+                continue;
+            };
+
+            let (x0, y0) = (key.origin.0 as i64, key.origin.1 as i64);
+            let (x1, y1) = (x0 + key.size.0 as i64 - 1, y0 + key.size.1 as i64 - 1);
+
+            for x in x0..=x1 {
+                put(x, y0);
+                put(x, y1);
+            }
+            for y in y0..=y1 {
+                put(x0, y);
+                put(x1, y);
+            }
+        }
+    }
 }
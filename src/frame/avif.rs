@@ -1,4 +1,7 @@
-pub fn load_avif(_bytes: &[u8]) -> eyre::Result<(u32, u32, Vec<Vec<u8>>)> {
+use crate::Size;
+
+// Returns RGBA8 frames and `pts` in milliseconds.
+pub fn load_avif(_bytes: &[u8]) -> eyre::Result<(Size, Vec<f32>, Vec<Vec<u8>>)> {
     #[cfg(feature = "de_avif")]
     {
         return feat::load_avif(_bytes);
@@ -9,8 +12,84 @@ pub fn load_avif(_bytes: &[u8]) -> eyre::Result<(u32, u32, Vec<Vec<u8>>)> {
 
 #[cfg(feature = "de_avif")]
 mod feat {
+    use crate::Size;
+    use libavif_sys::*;
+    use std::{ffi::CStr, mem, ptr};
+
+    struct Decoder(*mut avifDecoder);
+
+    impl Drop for Decoder {
+        fn drop(&mut self) {
+            unsafe { avifDecoderDestroy(self.0) }
+        }
+    }
+
+    fn check(res: avifResult) -> eyre::Result<()> {
+        if res == AVIF_RESULT_OK {
+            return Ok(());
+        }
+
+        let msg = unsafe { CStr::from_ptr(avifResultToString(res)) };
+        eyre::bail!("ERROR: libavif: {}", msg.to_string_lossy())
+    }
+
+    // Supports:
+    //   * image sequences(animated AVIF)
+    //   * alpha
+    //   * 10/12-bit, converted to 8-bit
     #[inline]
-    pub fn load_avif(bytes: &[u8]) -> eyre::Result<(u32, u32, Vec<Vec<u8>>)> {
-        todo!()
+    pub fn load_avif(bytes: &[u8]) -> eyre::Result<(Size, Vec<f32>, Vec<Vec<u8>>)> {
+        let decoder = Decoder(unsafe { avifDecoderCreate() });
+        if decoder.0.is_null() {
+            eyre::bail!("ERROR: libavif: avifDecoderCreate()")
+        }
+
+        unsafe {
+            check(avifDecoderSetIOMemory(
+                decoder.0,
+                bytes.as_ptr(),
+                bytes.len(),
+            ))?;
+            check(avifDecoderParse(decoder.0))?;
+        }
+
+        let (size, len) = unsafe {
+            let image = &*(*decoder.0).image;
+
+            (
+                Size::from_u32(image.width, image.height),
+                (*decoder.0).imageCount.max(1) as usize,
+            )
+        };
+
+        let mut data = Vec::with_capacity(len);
+        let mut pts = Vec::with_capacity(len);
+        for _ in 0..len {
+            unsafe {
+                check(avifDecoderNextImage(decoder.0))?;
+
+                // seconds -> ms
+                let timing = (*decoder.0).imageTiming;
+                pts.push((timing.duration * 1000.0) as f32);
+
+                let image = (*decoder.0).image;
+                let mut rgb: avifRGBImage = mem::zeroed();
+                avifRGBImageSetDefaults(&mut rgb, image);
+                rgb.format = AVIF_RGB_FORMAT_RGBA;
+                rgb.depth = 8;
+
+                let mut frame = vec![0_u8; rgb.width as usize * rgb.height as usize * 4];
+                rgb.pixels = frame.as_mut_ptr();
+                rgb.rowBytes = rgb.width * 4;
+
+                let res = avifImageYUVToRGB(image, &mut rgb);
+                rgb.pixels = ptr::null_mut();
+                check(res)?;
+
+                data.push(frame);
+            }
+        }
+
+        Ok((size, pts, data))
     }
 }
@@ -0,0 +1,67 @@
+use crate::Size;
+
+use ::exif::{In, Reader, Tag};
+use std::io::Cursor;
+
+// Returns the EXIF orientation, `1` if missing.
+//
+// 1: normal           2: mirror horizontal
+// 3: rotate 180       4: mirror vertical
+// 5: transpose        6: rotate 90 CW
+// 7: transverse       8: rotate 270 CW
+pub fn orientation(bytes: &[u8]) -> u32 {
+    let Ok(exif) = Reader::new().read_from_container(&mut Cursor::new(bytes)) else {
+        return 1;
+    };
+
+    exif.get_field(Tag::Orientation, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .filter(|v| (1..=8).contains(v))
+        .unwrap_or(1)
+}
+
+// width and height are swapped
+pub fn is_transposed(orientation: u32) -> bool {
+    (5..=8).contains(&orientation)
+}
+
+// Rotates or mirrors the RGBA8 image, `size` is the size before rotation.
+pub fn apply_orientation(data: &mut Vec<u8>, size: Size, orientation: u32) {
+    if orientation <= 1 || orientation > 8 {
+        return;
+    }
+
+    let (w, h) = (size.width() as usize, size.height() as usize);
+    let (dw, dh) = if is_transposed(orientation) {
+        (h, w)
+    } else {
+        (w, h)
+    };
+
+    if data.len() < w * h * 4 {
+        return;
+    }
+
+    let mut res = vec![0; dw * dh * 4];
+    for dy in 0..dh {
+        for dx in 0..dw {
+            let (sx, sy) = match orientation {
+                2 => (w - 1 - dx, dy),
+                3 => (w - 1 - dx, h - 1 - dy),
+                4 => (dx, h - 1 - dy),
+                5 => (dy, dx),
+                6 => (dy, h - 1 - dx),
+                7 => (w - 1 - dy, h - 1 - dx),
+                8 => (w - 1 - dy, dx),
+                _ => unreachable!(),
+            };
+
+            let src = (sy * w + sx) * 4;
+            let dst = (dy * dw + dx) * 4;
+
+            res[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+
+    *data = res;
+}
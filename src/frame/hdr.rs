@@ -0,0 +1,78 @@
+use crate::*;
+
+use esyn::EsynDe;
+
+// Shared by all decoding threads, changed by the exposure key.
+static TONE_MAP: RwLock<(ToneMap, f32)> = RwLock::new((ToneMap::Aces, 0.0));
+
+/// Maps linear HDR values into 8-bit sRGB.
+#[derive(Debug, Default, Clone, Copy, PartialEq, EsynDe)]
+pub enum ToneMap {
+    /// ACES filmic curve (Narkowicz fit)
+    #[default]
+    Aces,
+    /// `x / (1 + x)`
+    Reinhard,
+    /// Exposure only, values above 1.0 are clipped.
+    Exposure,
+}
+
+impl ToneMap {
+    #[inline]
+    fn apply(&self, x: f32) -> f32 {
+        match self {
+            Self::Aces => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+
+                (x * (a * x + b)) / (x * (c * x + d) + e)
+            }
+            Self::Reinhard => x / (1.0 + x),
+            Self::Exposure => x,
+        }
+    }
+}
+
+pub fn set_tone_map(op: ToneMap, exposure: f32) {
+    *TONE_MAP.write().unwrap() = (op, exposure);
+}
+
+/// `exposure` is in stops.
+pub fn add_exposure(stops: f32) -> f32 {
+    let mut tm = TONE_MAP.write().unwrap();
+    tm.1 += stops;
+
+    tm.1
+}
+
+// Radiance `.hdr` and OpenEXR, in RGBA8.
+pub fn load_hdr(bytes: &[u8]) -> eyre::Result<(Size, Vec<u8>)> {
+    let img = image::load_from_memory(bytes)?.to_rgba32f();
+    let size = Size::from_u32(img.width(), img.height());
+
+    let (op, exposure) = *TONE_MAP.read().unwrap();
+    let scale = 2.0_f32.powf(exposure);
+
+    let mut res = Vec::with_capacity(img.len());
+    for px in img.pixels() {
+        let [r, g, b, a] = px.0;
+
+        for v in [r, g, b] {
+            let v = op.apply((v * scale).max(0.0)).clamp(0.0, 1.0);
+
+            res.push((linear_to_srgb(v) * 255.0).round() as u8);
+        }
+
+        // alpha is linear
+        res.push((a.clamp(0.0, 1.0) * 255.0).round() as u8);
+    }
+
+    Ok((size, res))
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,58 @@
+use crate::*;
+
+use image::{io::Reader, ImageDecoder};
+use qcms::{DataType, Intent, Profile, Transform};
+use std::{io::Cursor, sync::OnceLock};
+
+// Set once at startup.
+static OUTPUT: OnceLock<Option<Box<Profile>>> = OnceLock::new();
+
+/// Converts into `output`, or sRGB if `None`.
+pub fn init(enabled: bool, output: Option<&Path>) -> eyre::Result<()> {
+    let profile = match (enabled, output) {
+        (false, ..) => None,
+        (true, None) => Some(Profile::new_sRGB()),
+        (true, Some(path)) => {
+            let bytes = std::fs::read(path)?;
+            let Some(profile) = Profile::new_from_slice(&bytes, false) else {
+                eyre::bail!("ERROR: Invalid ICC profile: {}", path.display())
+            };
+
+            Some(profile)
+        }
+    };
+
+    let _ = OUTPUT.set(profile);
+
+    Ok(())
+}
+
+// Embedded profile of formats supported by `image`.
+pub fn icc_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut decoder = Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?;
+
+    decoder.icc_profile().ok()?
+}
+
+/// RGBA8 transform from the embedded profile into the output profile.
+///
+/// `None` if there is no profile, or it is not RGB (e.g. gray, CMYK).
+pub fn new_transform(bytes: &[u8]) -> Option<Transform> {
+    let output = OUTPUT.get()?.as_ref()?;
+    let input = Profile::new_from_slice(&icc_profile(bytes)?, false)?;
+    if input.is_sRGB() && output.is_sRGB() {
+        return None;
+    }
+
+    Transform::new(&input, output, DataType::RGBA8, Intent::Perceptual)
+}
+
+pub fn transform(bytes: &[u8], data: &mut [u8]) {
+    if let Some(xfm) = new_transform(bytes) {
+        xfm.apply(data);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::Size;
+
+use jpeg_decoder::{Decoder, PixelFormat};
+
+/// Decodes with DCT scaling (1/2, 1/4, 1/8), at the smallest size not less than `dst_size`.
+///
+/// Sizes are before EXIF rotation. `None` if the source is not large enough,
+/// or the pixel format is not supported (e.g. CMYK), then decode it at full size.
+pub fn load_scaled(bytes: &[u8], dst_size: Size) -> Option<(Size, Vec<u8>)> {
+    let mut decoder = Decoder::new(bytes);
+    decoder.read_info().ok()?;
+
+    let info = decoder.info()?;
+    let (dw, dh) = (
+        dst_size.width().ceil().clamp(1.0, u16::MAX as f32) as u16,
+        dst_size.height().ceil().clamp(1.0, u16::MAX as f32) as u16,
+    );
+
+    let (w, h) = decoder.scale(dw, dh).ok()?;
+    if (w, h) == (info.width, info.height) {
+        return None;
+    }
+
+    let data = decoder.decode().ok()?;
+    let len = w as usize * h as usize;
+
+    let res: Vec<u8> = match decoder.info()?.pixel_format {
+        PixelFormat::RGB24 if data.len() >= len * 3 => data
+            .chunks_exact(3)
+            .take(len)
+            .flat_map(|v| [v[0], v[1], v[2], 255])
+            .collect(),
+
+        PixelFormat::L8 if data.len() >= len => data
+            .iter()
+            .take(len)
+            .flat_map(|&v| [v, v, v, 255])
+            .collect(),
+
+        _ => return None,
+    };
+
+    Some((Size::new(w as f32, h as f32), res))
+}
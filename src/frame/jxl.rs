@@ -0,0 +1,78 @@
+use crate::Size;
+
+// The loop count, `pts` in milliseconds and RGBA8 frames.
+type Decoded = (Size, u32, Vec<f32>, Vec<Vec<u8>>);
+
+pub fn load_jxl(_bytes: &[u8]) -> eyre::Result<Decoded> {
+    #[cfg(feature = "de_jxl")]
+    {
+        return feat::load_jxl(_bytes);
+    }
+
+    eyre::bail!("JPEG XL support is disabled, enable the `de_jxl` feature")
+}
+
+#[cfg(feature = "de_jxl")]
+mod feat {
+    use crate::Size;
+    use jxl_oxide::{FrameBuffer, JxlImage};
+    use std::io::Cursor;
+
+    #[inline]
+    pub fn load_jxl(bytes: &[u8]) -> eyre::Result<super::Decoded> {
+        let image = JxlImage::builder()
+            .read(Cursor::new(bytes))
+            .map_err(|e| eyre::eyre!(e))?;
+        let size = Size::from_u32(image.width(), image.height());
+
+        // ticks -> ms, `num_loops` is 0 for forever
+        let (tick, loops) = match &image.image_header().metadata.animation {
+            Some(anim) => (
+                1000.0 * anim.tps_denominator as f32 / anim.tps_numerator as f32,
+                anim.num_loops,
+            ),
+            None => (0.0, 0),
+        };
+
+        let len = image.num_loaded_keyframes();
+        let mut data = Vec::with_capacity(len);
+        let mut pts = Vec::with_capacity(len);
+        for index in 0..len {
+            let render = image.render_frame(index).map_err(|e| eyre::eyre!(e))?;
+
+            let mut stream = render.stream();
+            let (w, h, c) = (
+                stream.width() as usize,
+                stream.height() as usize,
+                stream.channels() as usize,
+            );
+            let mut fb = FrameBuffer::new(w, h, c);
+            stream.write_to_buffer(fb.buf_mut());
+
+            pts.push(render.duration() as f32 * tick);
+            data.push(to_rgba8(fb.buf(), c));
+        }
+
+        Ok((size, loops, pts, data))
+    }
+
+    // f32 [0.0, 1.0] -> u8
+    fn to_rgba8(buf: &[f32], channels: usize) -> Vec<u8> {
+        let f = |v: f32| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+
+        let mut res = Vec::with_capacity(buf.len() / channels * 4);
+        for px in buf.chunks_exact(channels) {
+            let rgba = match *px {
+                [l] => [f(l), f(l), f(l), 255],
+                [l, a] => [f(l), f(l), f(l), f(a)],
+                [r, g, b] => [f(r), f(g), f(b), 255],
+                [r, g, b, a, ..] => [f(r), f(g), f(b), f(a)],
+                _ => unreachable!(),
+            };
+
+            res.extend_from_slice(&rgba);
+        }
+
+        res
+    }
+}
@@ -0,0 +1,29 @@
+use ::zip::ZipArchive;
+use std::io::{Cursor, Read};
+
+// KRA (Krita) and ORA (OpenRaster) are zip files with a flattened PNG preview
+// of the whole canvas.
+const MERGED_IMAGE: &str = "mergedimage.png";
+
+// The first entry is an uncompressed `mimetype` file.
+pub fn is_kra(bytes: &[u8]) -> bool {
+    let Some(name) = bytes.get(30..38) else {
+        return false;
+    };
+    let mime = &bytes[38..];
+
+    bytes.starts_with(b"PK\x03\x04")
+        && name == b"mimetype"
+        && (mime.starts_with(b"application/x-krita") || mime.starts_with(b"image/openraster"))
+}
+
+// Returns the PNG bytes of the merged image.
+pub fn load_kra(bytes: &[u8]) -> eyre::Result<Vec<u8>> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+    let mut file = zip.by_name(MERGED_IMAGE)?;
+
+    let mut res = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut res)?;
+
+    Ok(res)
+}
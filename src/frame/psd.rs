@@ -0,0 +1,376 @@
+use crate::Size;
+
+// Only the merged composite of the image data section is decoded, layers are
+// ignored. The composite exists when "Maximize Compatibility" is enabled,
+// which is the default of Photoshop.
+//
+// https://www.adobe.com/devnet-apps/photoshop/fileformatashtml/
+
+// Color modes
+const BITMAP: u16 = 0;
+const GRAYSCALE: u16 = 1;
+const INDEXED: u16 = 2;
+const RGB: u16 = 3;
+const CMYK: u16 = 4;
+const MULTICHANNEL: u16 = 7;
+const DUOTONE: u16 = 8;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> eyre::Result<&'a [u8]> {
+        let Some(res) = self
+            .pos
+            .checked_add(n)
+            .and_then(|end| self.bytes.get(self.pos..end))
+        else {
+            eyre::bail!("ERROR: Unexpected end of PSD")
+        };
+        self.pos += n;
+
+        Ok(res)
+    }
+
+    fn left(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn u16(&mut self) -> eyre::Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> eyre::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> eyre::Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    // Length-prefixed section.
+    fn section(&mut self, is_psb: bool) -> eyre::Result<&'a [u8]> {
+        let len = if is_psb {
+            self.u64()? as usize
+        } else {
+            self.u32()? as usize
+        };
+
+        self.take(len)
+    }
+}
+
+pub fn load_psd(bytes: &[u8]) -> eyre::Result<(Size, Vec<u8>)> {
+    let mut r = Reader { bytes, pos: 0 };
+
+    if r.take(4)? != b"8BPS" {
+        eyre::bail!("ERROR: Not a PSD file")
+    }
+
+    // 1: PSD, 2: PSB (large document format)
+    let is_psb = r.u16()? == 2;
+    r.take(6)?;
+
+    let channels = r.u16()? as usize;
+    let height = r.u32()? as usize;
+    let width = r.u32()? as usize;
+    let depth = r.u16()? as usize;
+    let mode = r.u16()?;
+
+    if !matches!(depth, 1 | 8 | 16 | 32) {
+        eyre::bail!("ERROR: Unsupported PSD depth: {depth}")
+    }
+
+    // limits of the spec, checked before allocating
+    let max = if is_psb { 300_000 } else { 30_000 };
+    if !(1..=max).contains(&width) || !(1..=max).contains(&height) {
+        eyre::bail!("ERROR: Invalid PSD size: {width}x{height}")
+    }
+    if !(1..=56).contains(&channels) {
+        eyre::bail!("ERROR: Invalid PSD channels: {channels}")
+    }
+
+    let palette = r.section(false)?;
+    let _resources = r.section(false)?;
+    let has_alpha = has_merged_alpha(r.section(is_psb)?, is_psb);
+
+    // planar, one plane per channel
+    let row_len = (width * depth).div_ceil(8);
+    let planes = match r.u16()? {
+        // raw
+        0 => {
+            let data = r.take(row_len * height * channels)?;
+
+            data.chunks_exact(row_len * height)
+                .map(|p| p.to_vec())
+                .collect::<Vec<_>>()
+        }
+
+        // PackBits
+        1 => {
+            let rows = channels * height;
+            let mut table = Reader {
+                bytes: r.take(rows * if is_psb { 4 } else { 2 })?,
+                pos: 0,
+            };
+            let mut counts = Vec::with_capacity(rows);
+            for _ in 0..rows {
+                counts.push(if is_psb {
+                    table.u32()? as usize
+                } else {
+                    table.u16()? as usize
+                });
+            }
+
+            // A packet of 2 bytes is 128 bytes at most, so the planes are limited
+            // by the bytes left in the file.
+            let is_short = counts.iter().sum::<usize>() > r.left()
+                || counts.iter().any(|&len| len * 64 < row_len);
+            if is_short {
+                eyre::bail!("ERROR: Unexpected end of PSD")
+            }
+
+            let mut res = Vec::with_capacity(channels);
+            for c in 0..channels {
+                let mut plane = Vec::with_capacity(row_len * height);
+
+                for &len in counts[c * height..(c + 1) * height].iter() {
+                    let row = unpack_bits(r.take(len)?, row_len);
+                    plane.extend_from_slice(&row);
+                }
+
+                res.push(plane);
+            }
+
+            res
+        }
+
+        _ => eyre::bail!("ERROR: Unsupported PSD compression"),
+    };
+
+    // -> 8-bit
+    let sample = |c: usize, i: usize| -> u8 {
+        let plane = &planes[c];
+
+        match depth {
+            1 => {
+                let (y, x) = (i / width, i % width);
+                let bit = plane[y * row_len + x / 8] >> (7 - x % 8) & 1;
+
+                // 1 is black
+                if bit == 1 {
+                    0
+                } else {
+                    255
+                }
+            }
+            8 => plane[i],
+            16 => plane[i * 2],
+            32 => {
+                let v = f32::from_be_bytes(plane[i * 4..i * 4 + 4].try_into().unwrap());
+
+                // linear -> sRGB
+                (v.clamp(0.0, 1.0).powf(1.0 / 2.2) * 255.0) as u8
+            }
+            _ => 0,
+        }
+    };
+
+    let len = width * height;
+    let mut res = Vec::with_capacity(len * 4);
+
+    match mode {
+        RGB if channels >= 3 => {
+            for i in 0..len {
+                let a = if has_alpha && channels > 3 {
+                    sample(3, i)
+                } else {
+                    255
+                };
+
+                res.extend_from_slice(&[sample(0, i), sample(1, i), sample(2, i), a]);
+            }
+        }
+
+        // 0 is full ink
+        CMYK if channels >= 4 => {
+            for i in 0..len {
+                let k = sample(3, i) as u32;
+                let f = |v: u8| (v as u32 * k / 255) as u8;
+                let a = if has_alpha && channels > 4 {
+                    sample(4, i)
+                } else {
+                    255
+                };
+
+                res.extend_from_slice(&[f(sample(0, i)), f(sample(1, i)), f(sample(2, i)), a]);
+            }
+        }
+
+        INDEXED if palette.len() >= 768 => {
+            for i in 0..len {
+                let n = sample(0, i) as usize;
+
+                res.extend_from_slice(&[palette[n], palette[n + 256], palette[n + 512], 255]);
+            }
+        }
+
+        BITMAP | GRAYSCALE | DUOTONE | MULTICHANNEL => {
+            for i in 0..len {
+                let l = sample(0, i);
+                let a = if has_alpha && channels > 1 {
+                    sample(1, i)
+                } else {
+                    255
+                };
+
+                res.extend_from_slice(&[l, l, l, a]);
+            }
+        }
+
+        _ => eyre::bail!("ERROR: Unsupported PSD color mode: {mode}"),
+    }
+
+    Ok((Size::from_u32(width as u32, height as u32), res))
+}
+
+// A negative layer count means the first alpha channel is the transparency of
+// the merged result.
+fn has_merged_alpha(section: &[u8], is_psb: bool) -> bool {
+    let offset = if is_psb { 8 } else { 4 };
+
+    section
+        .get(offset..offset + 2)
+        .map(|v| i16::from_be_bytes([v[0], v[1]]) < 0)
+        .unwrap_or(false)
+}
+
+fn unpack_bits(src: &[u8], len: usize) -> Vec<u8> {
+    let mut res = Vec::with_capacity(len);
+    let mut i = 0;
+
+    while i < src.len() && res.len() < len {
+        let n = src[i] as i8;
+        i += 1;
+
+        match n {
+            // copy the next `n + 1` bytes
+            0.. => {
+                let end = (i + n as usize + 1).min(src.len());
+                res.extend_from_slice(&src[i..end]);
+                i = end;
+            }
+
+            // no-op
+            -128 => {}
+
+            // repeat the next byte `1 - n` times
+            _ => {
+                if let Some(&v) = src.get(i) {
+                    res.resize(res.len() + (1 - n as isize) as usize, v);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    res.resize(len, 0);
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_bits_packets() {
+        // literal 3, repeat 4, no-op, literal 1
+        let src = [0x02, 1, 2, 3, 0xfd, 9, 0x80, 0x00, 7];
+
+        assert_eq!(unpack_bits(&src, 8), [1, 2, 3, 9, 9, 9, 9, 7]);
+    }
+
+    #[test]
+    fn unpack_bits_broken() {
+        // truncated literal and repeat, padded with zeros
+        assert_eq!(unpack_bits(&[0x05, 1, 2], 4), [1, 2, 0, 0]);
+        assert_eq!(unpack_bits(&[0xfe], 3), [0, 0, 0]);
+
+        // longer than the row
+        assert_eq!(unpack_bits(&[0x81, 5], 4), [5, 5, 5, 5]);
+        assert_eq!(unpack_bits(&[], 2), [0, 0]);
+    }
+
+    #[test]
+    fn merged_alpha() {
+        let layer_count = |v: i16, is_psb: bool| {
+            let mut res = vec![0; if is_psb { 8 } else { 4 }];
+            res.extend_from_slice(&v.to_be_bytes());
+
+            res
+        };
+
+        assert!(has_merged_alpha(&layer_count(-2, false), false));
+        assert!(!has_merged_alpha(&layer_count(2, false), false));
+        assert!(has_merged_alpha(&layer_count(-1, true), true));
+        assert!(!has_merged_alpha(&layer_count(-1, true), false));
+
+        // no layer info
+        assert!(!has_merged_alpha(&[], false));
+        assert!(!has_merged_alpha(&[0; 5], false));
+    }
+
+    // RGB header followed by the PackBits compression method.
+    fn packbits_header(is_psb: bool, width: u32, height: u32, depth: u16) -> Vec<u8> {
+        let mut res = b"8BPS".to_vec();
+        res.extend_from_slice(&(if is_psb { 2u16 } else { 1 }).to_be_bytes());
+        res.extend_from_slice(&[0; 6]);
+        res.extend_from_slice(&3u16.to_be_bytes());
+        res.extend_from_slice(&height.to_be_bytes());
+        res.extend_from_slice(&width.to_be_bytes());
+        res.extend_from_slice(&depth.to_be_bytes());
+        res.extend_from_slice(&RGB.to_be_bytes());
+
+        // color mode, resources, layers
+        res.extend_from_slice(&[0; 8]);
+        res.extend_from_slice(&vec![0; if is_psb { 8 } else { 4 }]);
+
+        res.extend_from_slice(&1u16.to_be_bytes());
+
+        res
+    }
+
+    #[test]
+    fn packbits_rgb() {
+        let mut bytes = packbits_header(false, 2, 1, 8);
+        // one row per channel
+        bytes.extend_from_slice(&[0, 2, 0, 3, 0, 2]);
+        bytes.extend_from_slice(&[0xff, 10, 0x01, 20, 21, 0xff, 30]);
+
+        let (size, data) = load_psd(&bytes).unwrap();
+
+        assert_eq!(size, Size::from_u32(2, 1));
+        assert_eq!(data, [10, 20, 30, 255, 10, 21, 30, 255]);
+    }
+
+    #[test]
+    fn packbits_truncated() {
+        // no room for the row counts
+        let bytes = packbits_header(true, 300_000, 300_000, 32);
+        assert!(load_psd(&bytes).is_err());
+
+        // rows longer than the file
+        let mut bytes = packbits_header(false, 30_000, 30_000, 8);
+        bytes.extend_from_slice(&vec![0xff; 3 * 30_000 * 2]);
+        assert!(load_psd(&bytes).is_err());
+
+        // rows too short to fill the width
+        let mut bytes = packbits_header(false, 30_000, 1, 8);
+        bytes.extend_from_slice(&[0, 2, 0, 2, 0, 2]);
+        bytes.extend_from_slice(&[0x81, 0, 0x81, 0, 0x81, 0]);
+        assert!(load_psd(&bytes).is_err());
+    }
+}
@@ -1,41 +1,107 @@
 use crate::*;
 
-pub fn load_svg(_bytes: &[u8]) -> eyre::Result<(Size, Vec<Vec<u8>>)> {
+// used if the document has no size
+pub const SVG_DEFAULT_SIZE: Size = Size {
+    width: 800.0,
+    height: 600.0,
+};
+
+// SVG is plain text, so `imagesize` can not detect it.
+pub fn is_svg(bytes: &[u8]) -> bool {
+    // svgz
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        #[cfg(feature = "de_svg")]
+        {
+            return usvg::decompress_svgz(bytes).is_ok_and(|v| is_svg_text(&v));
+        }
+
+        #[cfg(not(feature = "de_svg"))]
+        return false;
+    }
+
+    is_svg_text(bytes)
+}
+
+fn is_svg_text(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(1024)];
+    let head = String::from_utf8_lossy(head).to_lowercase();
+
+    head.trim_start().starts_with('<') && head.contains("<svg")
+}
+
+pub fn svg_size(_bytes: &[u8]) -> eyre::Result<Size> {
+    #[cfg(feature = "de_svg")]
+    {
+        return feat::svg_size(_bytes);
+    }
+
+    eyre::bail!("SVG support is disabled, enable the `de_svg` feature")
+}
+
+// Rasterize at `dst_size` rather than scaling a bitmap.
+pub fn load_svg(_bytes: &[u8], _dst_size: Size) -> eyre::Result<Vec<u8>> {
     #[cfg(feature = "de_svg")]
     {
-        return feat::load_svg(_bytes);
+        return feat::load_svg(_bytes, _dst_size);
     }
 
-    eyre::bail!("")
+    eyre::bail!("SVG support is disabled, enable the `de_svg` feature")
 }
 
 #[cfg(feature = "de_svg")]
 mod feat {
-    use crate::img::Size;
-
-    pub fn load_svg(bytes: &[u8]) -> eyre::Result<(Size, Vec<Vec<u8>>)> {
-        use usvg::TreeParsing;
-
-        // TODO: default (width, height)
-        // window w,h
-        // BUG: font
-        let opt = usvg::Options::default();
-        let rtree = usvg::Tree::from_data(bytes, &opt).unwrap();
-        let pixmap_size = rtree.size.to_screen_size();
-        //.scale_to(usvg::ScreenSize::new(size.width, size.height).unwrap());
-
-        let size = Size::from_u32(pixmap_size.width(), pixmap_size.height());
-        //let mut pixmap = tiny_skia::Pixmap::new(size.width, size.height).unwrap();
-        let mut pixmap = tiny_skia::Pixmap::new(pixmap_size.width(), pixmap_size.height()).unwrap();
-
-        resvg::render(
-            &rtree,
-            resvg::FitTo::Original,
-            tiny_skia::Transform::default(),
-            pixmap.as_mut(),
-        )
-        .unwrap();
-
-        Ok((size, vec![pixmap.data().to_vec()]))
+    use super::SVG_DEFAULT_SIZE;
+    use crate::Size;
+    use std::sync::OnceLock;
+    use usvg::fontdb;
+
+    // BUG: font
+    static FONTDB: OnceLock<fontdb::Database> = OnceLock::new();
+
+    fn parse(bytes: &[u8]) -> eyre::Result<usvg::Tree> {
+        let fontdb = FONTDB.get_or_init(|| {
+            let mut db = fontdb::Database::new();
+            db.load_system_fonts();
+
+            db
+        });
+
+        let opt = usvg::Options {
+            default_size: usvg::Size::from_wh(SVG_DEFAULT_SIZE.width, SVG_DEFAULT_SIZE.height)
+                .unwrap(),
+            ..Default::default()
+        };
+
+        Ok(usvg::Tree::from_data(bytes, &opt, fontdb)?)
+    }
+
+    pub fn svg_size(bytes: &[u8]) -> eyre::Result<Size> {
+        let size = parse(bytes)?.size().to_int_size();
+
+        Ok(Size::from_u32(size.width(), size.height()))
+    }
+
+    pub fn load_svg(bytes: &[u8], dst_size: Size) -> eyre::Result<Vec<u8>> {
+        let rtree = parse(bytes)?;
+        let src = rtree.size();
+
+        let (dw, dh) = (dst_size.width() as u32, dst_size.height() as u32);
+        let Some(mut pixmap) = tiny_skia::Pixmap::new(dw, dh) else {
+            eyre::bail!("ERROR: Invalid size `{dw}x{dh}`")
+        };
+
+        let transform =
+            tiny_skia::Transform::from_scale(dw as f32 / src.width(), dh as f32 / src.height());
+        resvg::render(&rtree, transform, &mut pixmap.as_mut());
+
+        // premultiplied -> RGBA
+        let mut res = Vec::with_capacity(pixmap.data().len());
+        for px in pixmap.pixels() {
+            let c = px.demultiply();
+
+            res.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+        }
+
+        Ok(res)
     }
 }
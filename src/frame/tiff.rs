@@ -0,0 +1,145 @@
+use crate::Size;
+
+use ::tiff::{
+    decoder::{Decoder, DecodingResult},
+    tags::{PhotometricInterpretation, Tag},
+    ColorType,
+};
+use std::io::{Cursor, Read, Seek};
+
+// e.g. scanned books, only the IFD chain is read.
+pub fn page_nums<R>(reader: R) -> eyre::Result<usize>
+where
+    R: Read + Seek,
+{
+    let mut decoder = Decoder::new(reader)?;
+
+    let mut res = 1;
+    while decoder.more_images() {
+        decoder.next_image()?;
+        res += 1;
+    }
+
+    Ok(res)
+}
+
+pub fn page_size(bytes: &[u8], nth: usize) -> eyre::Result<Size> {
+    let mut decoder = Decoder::new(Cursor::new(bytes))?;
+    decoder.seek_to_image(nth)?;
+
+    let (w, h) = decoder.dimensions()?;
+
+    Ok(Size::from_u32(w, h))
+}
+
+// Returns the `nth` page in RGBA8.
+pub fn load_tiff(bytes: &[u8], nth: usize) -> eyre::Result<(Size, Vec<u8>)> {
+    let mut decoder = Decoder::new(Cursor::new(bytes))?;
+    decoder.seek_to_image(nth)?;
+
+    let (w, h) = decoder.dimensions()?;
+    let ty = decoder.colortype()?;
+
+    // `tiff` reads a byte per sample, so bilevel pages are unpacked here
+    if ty == ColorType::Gray(1) {
+        let white_is_zero = decoder.get_tag_u32(Tag::PhotometricInterpretation)?
+            == PhotometricInterpretation::WhiteIsZero.to_u16() as u32;
+        let buf = read_bilevel(&mut decoder, bytes)?;
+
+        return Ok((
+            Size::from_u32(w, h),
+            unpack_bilevel(&buf, w, h, white_is_zero)?,
+        ));
+    }
+
+    // 16-bit -> 8-bit
+    let buf: Vec<u8> = match decoder.read_image()? {
+        DecodingResult::U8(v) => v,
+        DecodingResult::U16(v) => v.iter().map(|p| (p >> 8) as u8).collect(),
+
+        _ => eyre::bail!("ERROR: Unsupported TIFF sample format"),
+    };
+
+    let len = w as usize * h as usize;
+    let mut res = Vec::with_capacity(len * 4);
+
+    match ty {
+        ColorType::Gray(8 | 16) => {
+            for &l in buf.iter() {
+                res.extend_from_slice(&[l, l, l, 255]);
+            }
+        }
+
+        ColorType::GrayA(8 | 16) => {
+            for px in buf.chunks_exact(2) {
+                res.extend_from_slice(&[px[0], px[0], px[0], px[1]]);
+            }
+        }
+
+        ColorType::RGB(8 | 16) => {
+            for px in buf.chunks_exact(3) {
+                res.extend_from_slice(&[px[0], px[1], px[2], 255]);
+            }
+        }
+
+        ColorType::RGBA(8 | 16) => res = buf,
+
+        ColorType::CMYK(8 | 16) => {
+            for px in buf.chunks_exact(4) {
+                let k = 255 - px[3] as u32;
+                let f = |c: u8| ((255 - c as u32) * k / 255) as u8;
+
+                res.extend_from_slice(&[f(px[0]), f(px[1]), f(px[2]), 255]);
+            }
+        }
+
+        _ => eyre::bail!("ERROR: Unsupported TIFF color type `{ty:?}`"),
+    }
+
+    Ok((Size::from_u32(w, h), res))
+}
+
+// Uncompressed strips, as-is.
+fn read_bilevel(decoder: &mut Decoder<Cursor<&[u8]>>, bytes: &[u8]) -> eyre::Result<Vec<u8>> {
+    let compression = decoder
+        .find_tag_unsigned::<u16>(Tag::Compression)?
+        .unwrap_or(1);
+    if compression != 1 {
+        eyre::bail!("ERROR: Unsupported compression `{compression}` of bilevel TIFF");
+    }
+
+    let offsets = decoder.get_tag_u64_vec(Tag::StripOffsets)?;
+    let counts = decoder.get_tag_u64_vec(Tag::StripByteCounts)?;
+
+    let mut res = vec![];
+    for (&offset, &count) in offsets.iter().zip(counts.iter()) {
+        let Some(strip) = bytes.get(offset as usize..offset.saturating_add(count) as usize) else {
+            eyre::bail!("ERROR: Invalid strip of bilevel TIFF");
+        };
+
+        res.extend_from_slice(strip);
+    }
+
+    Ok(res)
+}
+
+// Packed by row, `1` is white unless `white_is_zero`.
+fn unpack_bilevel(buf: &[u8], w: u32, h: u32, white_is_zero: bool) -> eyre::Result<Vec<u8>> {
+    let (w, h) = (w as usize, h as usize);
+    let stride = (w + 7) / 8;
+    if buf.len() < stride * h {
+        eyre::bail!("ERROR: Truncated bilevel TIFF");
+    }
+
+    let mut res = Vec::with_capacity(w * h * 4);
+    for y in 0..h {
+        for x in 0..w {
+            let bit = buf[y * stride + x / 8] >> (7 - x % 8) & 1;
+            let l = if (bit == 1) != white_is_zero { 255 } else { 0 };
+
+            res.extend_from_slice(&[l, l, l, 255]);
+        }
+    }
+
+    Ok(res)
+}
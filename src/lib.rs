@@ -8,12 +8,12 @@ pub mod utils;
 
 pub use {
     app::{
-        buffer::*, canvas::*, draw::*, gesture::*, layout::*, page::*, state::*, task::*, view::*,
-        window::*, *,
+        buffer::*, cache::*, canvas::*, draw::*, gesture::*, layout::*, page::*, state::*, task::*,
+        view::*, window::*, *,
     },
     archive::*,
-    data::{config::*, *},
-    frame::*,
+    data::{config::*, playlist::*, *},
+    frame::{hdr::ToneMap, *},
     shape::{circle::*, rect::*},
     ui::{align::*, elem::*, style::*, *},
     utils::{affine::*, filter::*, size::*, vec2::*, *},
@@ -28,12 +28,27 @@ pub use std::{
 };
 // ==========================================
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+#[rustfmt::skip]
 pub const SUPPORTED_FORMAT: &[&str] = &[
     "jpg", "jpeg", //
-    "png",  //
+    "png", "apng", //
+    "tif", "tiff", //
+    "hdr", "exr", //
+    "psd", "psb", //
+    "kra", "ora", //
+    "bmp", //
+    "qoi", //
+    "ico", //
+    "pbm", "pgm", "ppm", "pnm", "pam", //
     "heic", "heif", //
     "avif", //
     "ase", "aseprite", //
     "gif",      //
+    "jxl",      //
+    #[cfg(feature = "de_svg")]
     "svg",
 ];
+pub const ARCHIVE_FORMAT: &[&str] = &[
+    "zip", "cbz", //
+    "tar", "cbt",
+];
@@ -1,6 +1,7 @@
 pub mod align;
 pub mod elem;
 pub mod style;
+pub mod text;
 
 use crate::*;
 
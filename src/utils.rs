@@ -7,3 +7,90 @@ pub mod vec2;
 pub fn is_similar(a: f32, b: f32) -> bool {
     (a - b).abs() <= a.abs() * f32::EPSILON
 }
+
+// e.g. vol2 < vol10
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        let (Some(&x), Some(&y)) = (a.peek(), b.peek()) else {
+            return a.peek().is_some().cmp(&b.peek().is_some());
+        };
+
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let mut lhs = String::new();
+            while let Some(c) = a.next_if(|c| c.is_ascii_digit()) {
+                lhs.push(c);
+            }
+            let mut rhs = String::new();
+            while let Some(c) = b.next_if(|c| c.is_ascii_digit()) {
+                rhs.push(c);
+            }
+
+            // compare without leading zeros
+            let l = lhs.trim_start_matches('0');
+            let r = rhs.trim_start_matches('0');
+            let res = l.len().cmp(&r.len()).then_with(|| l.cmp(r));
+
+            if res != Ordering::Equal {
+                return res;
+            }
+        } else {
+            let res = x.cmp(&y);
+
+            if res != Ordering::Equal {
+                return res;
+            }
+
+            a.next();
+            b.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn natural_order() {
+        let mut list = vec![
+            "vol10.cbz",
+            "vol2.cbz",
+            "vol1a.cbz",
+            "vol01.cbz",
+            "vol1.cbz",
+            "a10b2",
+            "a10b10",
+            "a2b30",
+            "vol",
+        ];
+        list.sort_by(|a, b| natural_cmp(a, b));
+
+        assert_eq!(
+            list,
+            [
+                "a2b30",
+                "a10b2",
+                "a10b10",
+                "vol",
+                "vol01.cbz",
+                "vol1.cbz",
+                "vol1a.cbz",
+                "vol2.cbz",
+                "vol10.cbz",
+            ]
+        );
+    }
+
+    #[test]
+    fn natural_leading_zeros() {
+        assert_eq!(natural_cmp("p007", "p7"), Ordering::Equal);
+        assert_eq!(natural_cmp("p007x", "p7y"), Ordering::Less);
+        assert_eq!(natural_cmp("x9", "x09z"), Ordering::Less);
+    }
+}